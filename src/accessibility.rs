@@ -0,0 +1,115 @@
+//! Describes the GUI to the screen readers.
+//!
+//! iced doesn't expose its widget tree or its layout, so the program describes its controls
+//! with [`AccessibilityNode`]s, which the window publishes from its view.
+//!
+//! [`AccessibilityNode`]: struct.AccessibilityNode.html
+
+use iced_winit::{Rectangle, Size};
+
+#[cfg(target_os = "macos")]
+use cocoa::{
+    base::id,
+    foundation::{NSPoint, NSRect, NSSize},
+};
+
+#[cfg(target_os = "macos")]
+use crate::cocoa_util;
+
+/// What kind of control an [`AccessibilityNode`] is.
+///
+/// [`AccessibilityNode`]: struct.AccessibilityNode.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessibilityRole {
+    Button,
+    Slider,
+    StaticText,
+    TextField,
+    Group,
+}
+
+impl AccessibilityRole {
+    /// The `NSAccessibilityRole` of the role.
+    #[cfg(target_os = "macos")]
+    fn ns_role(self) -> &'static str {
+        match self {
+            AccessibilityRole::Button => "AXButton",
+            AccessibilityRole::Slider => "AXSlider",
+            AccessibilityRole::StaticText => "AXStaticText",
+            AccessibilityRole::TextField => "AXTextField",
+            AccessibilityRole::Group => "AXGroup",
+        }
+    }
+}
+
+/// A control of the program, as a screen reader announces it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibilityNode {
+    pub role: AccessibilityRole,
+    /// What the control is for, e.g. "Amp".
+    pub label: String,
+    /// The current value, e.g. "0.50" for a slider.
+    pub value: Option<String>,
+    /// The logical bounds of the control in the window, for the screen reader to highlight it.
+    /// `None` uses the bounds of the window.
+    pub bounds: Option<Rectangle>,
+}
+
+/// Publishes the `nodes` as the accessibility children of the view of the `window` of the
+/// logical `size`, whose logical units are `points_per_unit` points.
+#[cfg(target_os = "macos")]
+pub(crate) fn publish(
+    window: &iced_winit::winit::window::Window,
+    size: Size,
+    points_per_unit: f64,
+    nodes: &[AccessibilityNode],
+) {
+    use iced_winit::winit::platform::macos::WindowExtMacOS;
+
+    let view = window.ns_view() as id;
+
+    unsafe {
+        let elements: Vec<id> = nodes
+            .iter()
+            .map(|node| {
+                let bounds = node.bounds.unwrap_or(Rectangle {
+                    x: 0.0,
+                    y: 0.0,
+                    width: size.width,
+                    height: size.height,
+                });
+
+                // the view isn't flipped, its origin is at the bottom left
+                let frame = NSRect::new(
+                    NSPoint::new(
+                        bounds.x as f64 * points_per_unit,
+                        (size.height - bounds.y - bounds.height) as f64 * points_per_unit,
+                    ),
+                    NSSize::new(
+                        bounds.width as f64 * points_per_unit,
+                        bounds.height as f64 * points_per_unit,
+                    ),
+                );
+
+                cocoa_util::create_accessibility_element(
+                    view,
+                    node.role.ns_role(),
+                    &node.label,
+                    node.value.as_deref(),
+                    frame,
+                )
+            })
+            .collect();
+
+        cocoa_util::set_accessibility_children(view, &elements);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn publish(
+    _window: &iced_winit::winit::window::Window,
+    _size: Size,
+    _points_per_unit: f64,
+    _nodes: &[AccessibilityNode],
+) {
+}
@@ -0,0 +1,214 @@
+use std::io::Cursor;
+
+use iced_wgpu::wgpu;
+use iced_winit::{Rectangle, Size};
+
+/// Draws a texture of the host into the frame, under the program, see
+/// [`ChildWindow::set_background_texture`].
+///
+/// [`ChildWindow::set_background_texture`]: struct.ChildWindow.html#method.set_background_texture
+pub(crate) struct Background {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    // the scale of the quad in clip space, which letterboxes the texture
+    scale: wgpu::Buffer,
+    texture: Option<(wgpu::BindGroup, Size<u32>)>,
+}
+
+impl Background {
+    /// Creates the pipeline drawing into frames of the `format`.
+    pub(crate) fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            bindings: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        dimension: wgpu::TextureViewDimension::D2,
+                        component_type: wgpu::TextureComponentType::Float,
+                        multisampled: false,
+                    },
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler { comparison: false },
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&bind_group_layout],
+        });
+
+        // compiled from the GLSL next to them, e.g. with `glslangValidator -V`
+        let vertex_shader = create_shader(device, include_bytes!("shader/background.vert.spv"));
+        let fragment_shader = create_shader(device, include_bytes!("shader/background.frag.spv"));
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            layout: &layout,
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: &vertex_shader,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: &fragment_shader,
+                entry_point: "main",
+            }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: wgpu::CullMode::None,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            }),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleStrip,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format,
+                color_blend: wgpu::BlendDescriptor::REPLACE,
+                alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            depth_stencil_state: None,
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[],
+            },
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 0.0,
+            compare: wgpu::CompareFunction::Always,
+        });
+
+        let scale = device.create_buffer_with_data(
+            &scale_bytes([1.0, 1.0]),
+            wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+        );
+
+        Background {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            scale,
+            texture: None,
+        }
+    }
+
+    /// Draws the `view` of a texture of the `size` from now on.
+    pub(crate) fn set_texture(
+        &mut self,
+        device: &wgpu::Device,
+        view: &wgpu::TextureView,
+        size: Size<u32>,
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.bind_group_layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::Binding {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: &self.scale,
+                        range: 0..SCALE_SIZE,
+                    },
+                },
+            ],
+        });
+
+        self.texture = Some((bind_group, size));
+    }
+
+    /// Draws the texture into the `frame` of the `frame_size`, scaled to fit it with its aspect
+    /// ratio, and only into the physical `region` of the frame if there is one. The rest of the
+    /// frame keeps the clear color.
+    pub(crate) fn draw(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        frame: &wgpu::TextureView,
+        frame_size: Size<u32>,
+        region: Option<Rectangle<u32>>,
+    ) {
+        let (bind_group, size) = match &self.texture {
+            Some(texture) => texture,
+            None => return,
+        };
+
+        if size.width == 0 || size.height == 0 || frame_size.width == 0 || frame_size.height == 0 {
+            return;
+        }
+
+        let fit = (frame_size.width as f32 / size.width as f32)
+            .min(frame_size.height as f32 / size.height as f32);
+        let scale = [
+            size.width as f32 * fit / frame_size.width as f32,
+            size.height as f32 * fit / frame_size.height as f32,
+        ];
+
+        // wgpu can't write to a buffer from the queue yet
+        let staging =
+            device.create_buffer_with_data(&scale_bytes(scale), wgpu::BufferUsage::COPY_SRC);
+        encoder.copy_buffer_to_buffer(&staging, 0, &self.scale, 0, SCALE_SIZE);
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: frame,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Load,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: wgpu::Color::TRANSPARENT,
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        if let Some(region) = region {
+            pass.set_scissor_rect(region.x, region.y, region.width, region.height);
+        }
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.draw(0..4, 0..1);
+    }
+}
+
+/// The size of the `vec2` scale uniform.
+const SCALE_SIZE: wgpu::BufferAddress = 8;
+
+fn scale_bytes([x, y]: [f32; 2]) -> [u8; 8] {
+    let mut bytes = [0; 8];
+    bytes[..4].copy_from_slice(&x.to_ne_bytes());
+    bytes[4..].copy_from_slice(&y.to_ne_bytes());
+    bytes
+}
+
+fn create_shader(device: &wgpu::Device, spirv: &[u8]) -> wgpu::ShaderModule {
+    let spirv = wgpu::read_spirv(Cursor::new(spirv)).expect("the shaders are valid SPIR-V");
+
+    device.create_shader_module(&spirv)
+}
@@ -0,0 +1,347 @@
+use iced_wgpu::{wgpu, Renderer};
+use iced_winit::{winit::event_loop::EventLoopWindowTarget, Program, Size};
+
+use crate::{
+    ChildWindow, InitError, MessageSender, MetalLayerOptions, ParentSurface, RenderSchedule,
+};
+#[cfg(all(target_os = "macos", feature = "iosurface"))]
+use crate::{IOSurfaceTarget, OffscreenRenderer};
+
+/// Configures and creates a [`ChildWindow`].
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+pub struct ChildWindowBuilder<P: Program> {
+    pub(crate) program: P,
+    pub(crate) frame: Option<Size>,
+    pub(crate) min_size: Option<Size>,
+    pub(crate) max_size: Option<Size>,
+    pub(crate) present_mode: wgpu::PresentMode,
+    pub(crate) power_preference: wgpu::PowerPreference,
+    pub(crate) backends: wgpu::BackendBit,
+    pub(crate) anisotropic_filtering: bool,
+    pub(crate) limits: wgpu::Limits,
+    pub(crate) clear_color: wgpu::Color,
+    pub(crate) render_schedule: RenderSchedule,
+    pub(crate) sample_count: u32,
+    pub(crate) default_font: Option<&'static [u8]>,
+    pub(crate) transparent: bool,
+    pub(crate) depth_buffer: bool,
+    pub(crate) damage_tracking: bool,
+    pub(crate) hdr: bool,
+    pub(crate) force_srgb: Option<bool>,
+    pub(crate) metal_layer: MetalLayerOptions,
+    pub(crate) claim_tab: bool,
+    pub(crate) always_on_top: bool,
+    pub(crate) device: Option<(wgpu::Device, wgpu::Queue, wgpu::AdapterInfo)>,
+    pub(crate) runtime: Option<MessageSender<P::Message>>,
+}
+
+impl<P: 'static + Program<Renderer = Renderer>> ChildWindowBuilder<P> {
+    /// Creates a builder of a child window running the `program`.
+    pub fn new(program: P) -> Self {
+        ChildWindowBuilder {
+            program,
+            frame: None,
+            min_size: None,
+            max_size: None,
+            present_mode: wgpu::PresentMode::Mailbox,
+            power_preference: wgpu::PowerPreference::LowPower,
+            backends: wgpu::BackendBit::PRIMARY,
+            anisotropic_filtering: false,
+            limits: wgpu::Limits::default(),
+            clear_color: wgpu::Color::TRANSPARENT,
+            render_schedule: RenderSchedule::default(),
+            sample_count: 1,
+            default_font: None,
+            transparent: false,
+            depth_buffer: false,
+            damage_tracking: false,
+            hdr: false,
+            force_srgb: None,
+            metal_layer: MetalLayerOptions::default(),
+            claim_tab: false,
+            always_on_top: false,
+            device: None,
+            runtime: None,
+        }
+    }
+
+    /// Sets the logical size of the child window. It fills the parent by default, a standalone
+    /// window is 500×400.
+    pub fn frame(mut self, frame: Size) -> Self {
+        self.frame = Some(frame);
+        self
+    }
+
+    /// Sets the minimal logical size of the child window, and of the content of the parent on
+    /// macOS, so the host can't shrink the GUI until it breaks. Unconstrained by default.
+    pub fn min_size(mut self, min_size: Size) -> Self {
+        self.min_size = Some(min_size);
+        self
+    }
+
+    /// Sets the maximal logical size of the child window, and of the content of the parent on
+    /// macOS. Unconstrained by default.
+    pub fn max_size(mut self, max_size: Size) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Sets the present mode of the swap chain. `Mailbox` by default.
+    pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Sets the power preference of the requested adapter. `LowPower` by default, which keeps
+    /// laptops on the integrated GPU. Use `HighPerformance` for heavy custom rendering.
+    pub fn power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// Sets the backends the adapter is requested from. `PRIMARY` by default, i.e. Metal on
+    /// macOS.
+    ///
+    /// Force e.g. `VULKAN` for MoltenVK or `GL` to debug a backend. Building fails with
+    /// [`InitError::NoAdapter`] if none of them has a compatible adapter. The selected backend is
+    /// logged and reported by [`ChildWindow::gpu_info`].
+    ///
+    /// [`InitError::NoAdapter`]: enum.InitError.html#variant.NoAdapter
+    /// [`ChildWindow::gpu_info`]: struct.ChildWindow.html#method.gpu_info
+    pub fn backend(mut self, backends: wgpu::BackendBit) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// Requests the anisotropic filtering extension with the device. `false` by default.
+    ///
+    /// wgpu can't tell us whether the adapter supports it, the samplers just filter without it if
+    /// it doesn't.
+    pub fn anisotropic_filtering(mut self, anisotropic_filtering: bool) -> Self {
+        self.anisotropic_filtering = anisotropic_filtering;
+        self
+    }
+
+    /// Sets the limits requested with the device, e.g. more bind groups for a custom underlay.
+    /// The default limits by default.
+    ///
+    /// The limits above the ones wgpu supports are lowered to them with a warning.
+    pub fn limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the initial linear clear color, see [`linear_color`]. Transparent by default.
+    ///
+    /// [`linear_color`]: fn.linear_color.html
+    pub fn clear_color(mut self, clear_color: wgpu::Color) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Sets when the window is redrawn. `OnDemand` by default.
+    pub fn render_schedule(mut self, render_schedule: RenderSchedule) -> Self {
+        self.render_schedule = render_schedule;
+        self
+    }
+
+    /// Sets the number of MSAA samples: 1, 2, 4, 8 or 16. 1, i.e. no anti-aliasing, by default.
+    ///
+    /// iced multisamples into its own texture and resolves it into the frame, because its
+    /// pipelines render to single-sampled targets. The quads and text are always smooth, so
+    /// it affects the meshes, like the ones of `canvas`.
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Sets the font the program's text is rendered with by default, from the bytes of a TTF or
+    /// OTF file. iced's built-in font is used by default.
+    ///
+    /// The bytes have to be `'static`, because the renderer keeps referencing them, so embed the
+    /// font with `include_bytes!` or leak it.
+    pub fn font(mut self, font: &'static [u8]) -> Self {
+        self.default_font = Some(font);
+        self
+    }
+
+    /// Makes the window transparent, so the content of the parent shows through where iced
+    /// draws nothing. `false` by default.
+    ///
+    /// The frame is cleared with a fully transparent color, ignoring [`clear_color`], and the
+    /// Metal layer of the view is made non-opaque. Core Animation composites the layer as
+    /// premultiplied alpha, which is what iced blending produces over a transparent frame.
+    ///
+    /// [`clear_color`]: #method.clear_color
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// Creates a `Depth32Float` depth texture along with the swap chain, for custom 3D content
+    /// rendered under iced. `false` by default.
+    ///
+    /// iced doesn't use it, the frame is just cleared with it. See [`ChildWindow::depth_view`].
+    ///
+    /// [`ChildWindow::depth_view`]: struct.ChildWindow.html#method.depth_view
+    pub fn depth_buffer(mut self, depth_buffer: bool) -> Self {
+        self.depth_buffer = depth_buffer;
+        self
+    }
+
+    /// Redraws only the region of a frame that has changed since the last one. `false` by
+    /// default.
+    ///
+    /// The frames are kept in a texture, which is drawn into the swap chain. iced's primitives
+    /// are compared with the ones of the last frame, and the ones that differ are redrawn with a
+    /// scissor rect, on top of the last frame. It saves the GPU work of mostly static GUIs, a
+    /// slider dragged across a large view redraws only itself. The frames are still redrawn
+    /// whole with the debug overlay or a [`ChildWindow::set_pre_render`] callback, which draw
+    /// outside of the primitives.
+    ///
+    /// [`ChildWindow::set_pre_render`]: struct.ChildWindow.html#method.set_pre_render
+    pub fn damage_tracking(mut self, damage_tracking: bool) -> Self {
+        self.damage_tracking = damage_tracking;
+        self
+    }
+
+    /// Presents in an HDR format, `Rgba16Float` or else `Rgb10a2Unorm`, if the backend has one,
+    /// and in the usual 8-bit sRGB format otherwise. `false` by default.
+    ///
+    /// iced renders into the format with linear colors, which the Metal layer of the view
+    /// interprets in the extended linear sRGB color space. The gamut stays sRGB's, the program
+    /// only reaches beyond it with components outside of 0 and 1, and 1 stays the brightness of
+    /// SDR white, brighter colors need a display with headroom. On Windows the float swap chain
+    /// is scRGB, which is the same encoding. See [`ChildWindow::format`] for the one selected.
+    ///
+    /// Frames can't be captured with an HDR format.
+    ///
+    /// [`ChildWindow::format`]: struct.ChildWindow.html#method.format
+    pub fn hdr(mut self, hdr: bool) -> Self {
+        self.hdr = hdr;
+        self
+    }
+
+    /// Forces an sRGB swap chain format with `true`, or a linear one with `false`, instead of
+    /// the one preferred for the backend, e.g. to work around a driver. An sRGB one is preferred
+    /// by default, HDR ones aren't selected if it's `true`.
+    ///
+    /// iced's shaders output linear colors, and so do the clear colors of [`linear_color`]. An
+    /// sRGB format encodes them for the display. The Metal layer of a view with a linear format
+    /// is tagged with the linear sRGB color space instead, so the colors look the same. Other
+    /// platforms present a linear format as it is, so force one there only for a host that
+    /// encodes the frames itself.
+    ///
+    /// [`linear_color`]: fn.linear_color.html
+    pub fn force_srgb(mut self, srgb: bool) -> Self {
+        self.force_srgb = Some(srgb);
+        self
+    }
+
+    /// Tunes the `CAMetalLayer` backing the view on macOS, for the latency and for hosts that
+    /// composite with Core Animation too. wgpu's configuration by default, see
+    /// [`MetalLayerOptions`].
+    ///
+    /// [`MetalLayerOptions`]: struct.MetalLayerOptions.html
+    pub fn metal_layer(mut self, metal_layer: MetalLayerOptions) -> Self {
+        self.metal_layer = metal_layer;
+        self
+    }
+
+    /// Claims the Tab key for the window while it has the keyboard focus, for hosts that move
+    /// the focus between their own controls with it. `false` by default.
+    ///
+    /// On Windows the window answers `WM_GETDLGCODE` with `DLGC_WANTTAB`, so the dialog loop of
+    /// the host leaves Tab to it. On macOS the view receives Tab as the first responder already.
+    /// See [`ChildWindow::set_tab_message`].
+    ///
+    /// [`ChildWindow::set_tab_message`]: struct.ChildWindow.html#method.set_tab_message
+    pub fn claim_tab(mut self, claim_tab: bool) -> Self {
+        self.claim_tab = claim_tab;
+        self
+    }
+
+    /// Keeps the window above the other windows, e.g. for an editor floating above the host.
+    /// `false` by default. See [`ChildWindow::set_always_on_top`].
+    ///
+    /// [`ChildWindow::set_always_on_top`]: struct.ChildWindow.html#method.set_always_on_top
+    pub fn always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    /// Renders with the `device` and `queue` of the host instead of requesting our own, so the
+    /// host can submit everything on a single queue.
+    ///
+    /// `info` is the info of the adapter they belong to. wgpu doesn't let us query the formats
+    /// a surface or a device supports, so the swap chain format is selected from the ones of its
+    /// backend, which must be able to present to the window. The build fails with
+    /// [`InitError::UnsupportedFormat`] if none of them fits the options, instead of a panic of
+    /// wgpu. [`power_preference`] and [`backend`] are ignored then.
+    ///
+    /// [`InitError::UnsupportedFormat`]: enum.InitError.html#variant.UnsupportedFormat
+    /// [`power_preference`]: #method.power_preference
+    /// [`backend`]: #method.backend
+    pub fn with_device(
+        mut self,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        info: wgpu::AdapterInfo,
+    ) -> Self {
+        self.device = Some((device, queue, info));
+        self
+    }
+
+    /// Runs the `Command`s the program returns from its updates on a thread pool, and its
+    /// subscriptions, see [`ChildWindow::set_subscription`]. The messages they produce are sent
+    /// with the `sender`, so they arrive as `Event::UserEvent` to its event loop.
+    ///
+    /// Without a runtime no executor is started, for programs that don't need one. The commands
+    /// are passed to the callback of [`ChildWindow::set_on_command`] then if one is set, and
+    /// dropped otherwise.
+    ///
+    /// [`ChildWindow::set_subscription`]: struct.ChildWindow.html#method.set_subscription
+    /// [`ChildWindow::set_on_command`]: struct.ChildWindow.html#method.set_on_command
+    pub fn with_runtime(mut self, sender: MessageSender<P::Message>) -> Self {
+        self.runtime = Some(sender);
+        self
+    }
+
+    /// Creates the child window and attaches it to the `parent`.
+    pub fn build<T>(
+        self,
+        event_loop: &EventLoopWindowTarget<T>,
+        parent: &dyn ParentSurface,
+    ) -> Result<ChildWindow<P>, InitError> {
+        ChildWindow::build(event_loop, Some(parent), self)
+    }
+
+    /// Creates the window as a decorated top-level window of its own, see
+    /// [`ChildWindow::standalone`].
+    ///
+    /// [`ChildWindow::standalone`]: struct.ChildWindow.html#method.standalone
+    pub fn build_standalone<T>(
+        self,
+        event_loop: &EventLoopWindowTarget<T>,
+    ) -> Result<ChildWindow<P>, InitError> {
+        ChildWindow::build(event_loop, None, self)
+    }
+
+    /// Creates a target rendering the program into an `IOSurface` of the physical `size`, which
+    /// the host composites, instead of a window. See [`IOSurfaceTarget`].
+    ///
+    /// Only the [`backend`] and the [`clear_color`] apply to it, the other options configure the
+    /// window.
+    ///
+    /// [`IOSurfaceTarget`]: struct.IOSurfaceTarget.html
+    /// [`backend`]: #method.backend
+    /// [`clear_color`]: #method.clear_color
+    #[cfg(all(target_os = "macos", feature = "iosurface"))]
+    pub fn build_iosurface(self, size: Size<u32>) -> Result<IOSurfaceTarget<P>, InitError> {
+        let mut renderer = OffscreenRenderer::with_backends(self.program, self.backends)?;
+        renderer.set_clear_color(self.clear_color);
+
+        Ok(IOSurfaceTarget::new(renderer, size))
+    }
+}
@@ -0,0 +1,140 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use iced_wgpu::wgpu;
+use iced_winit::{futures, Size};
+
+use crate::CaptureError;
+
+/// The alignment of the rows of a texture copied to a buffer.
+const BYTES_PER_ROW_ALIGNMENT: u32 = 256;
+
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// Creates a texture of the physical `size`, which can be rendered to and read back.
+pub(crate) fn create_target(
+    device: &wgpu::Device,
+    size: Size<u32>,
+    format: wgpu::TextureFormat,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: extent(size),
+        array_layer_count: 1,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+    })
+}
+
+/// Submits the `encoder` with a copy of the `texture` and returns its pixels as tightly packed
+/// RGBA rows.
+pub(crate) fn read_pixels(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    mut encoder: wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    size: Size<u32>,
+    format: wgpu::TextureFormat,
+) -> Result<Vec<u8>, wgpu::BufferAsyncErr> {
+    // wgpu requires the rows of the copy to be aligned, so the buffer rows are padded
+    let unpadded_bytes_per_row = size.width * BYTES_PER_PIXEL;
+    let padded_bytes_per_row = (unpadded_bytes_per_row + BYTES_PER_ROW_ALIGNMENT - 1)
+        / BYTES_PER_ROW_ALIGNMENT
+        * BYTES_PER_ROW_ALIGNMENT;
+    let buffer_size = (padded_bytes_per_row * size.height) as wgpu::BufferAddress;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: buffer_size,
+        usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+    });
+
+    encoder.copy_texture_to_buffer(
+        wgpu::TextureCopyView {
+            texture,
+            mip_level: 0,
+            array_layer: 0,
+            origin: wgpu::Origin3d { x: 0, y: 0, z: 0 },
+        },
+        wgpu::BufferCopyView {
+            buffer: &buffer,
+            offset: 0,
+            bytes_per_row: padded_bytes_per_row,
+            rows_per_image: size.height,
+        },
+        extent(size),
+    );
+
+    queue.submit(&[encoder.finish()]);
+
+    let mapping = buffer.map_read(0, buffer_size);
+    device.poll(wgpu::Maintain::Wait);
+    let mapping = futures::executor::block_on(mapping)?;
+
+    let mut pixels: Vec<u8> = mapping
+        .as_slice()
+        .chunks(padded_bytes_per_row as usize)
+        .flat_map(|row| &row[..unpadded_bytes_per_row as usize])
+        .copied()
+        .collect();
+
+    if is_bgra(format) {
+        for pixel in pixels.chunks_mut(BYTES_PER_PIXEL as usize) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    Ok(pixels)
+}
+
+/// Writes the RGBA `pixels` of the physical `size` to a PNG file.
+pub(crate) fn write_png(path: &Path, size: Size<u32>, pixels: &[u8]) -> Result<(), CaptureError> {
+    let file = File::create(path)?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), size.width, size.height);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+
+    Ok(())
+}
+
+/// Reads an 8-bit RGBA PNG file, e.g. one written with [`write_png`].
+///
+/// Returns its physical size and pixels, or `None` if it has another color type or bit depth.
+///
+/// [`write_png`]: fn.write_png.html
+pub(crate) fn read_png(path: &Path) -> Result<Option<(Size<u32>, Vec<u8>)>, CaptureError> {
+    let decoder = png::Decoder::new(File::open(path)?);
+    let (info, mut reader) = decoder.read_info()?;
+
+    if info.color_type != png::ColorType::RGBA || info.bit_depth != png::BitDepth::Eight {
+        return Ok(None);
+    }
+
+    let mut pixels = vec![0; info.buffer_size()];
+    reader.next_frame(&mut pixels)?;
+
+    Ok(Some((Size::new(info.width, info.height), pixels)))
+}
+
+fn extent(size: Size<u32>) -> wgpu::Extent3d {
+    wgpu::Extent3d {
+        width: size.width,
+        height: size.height,
+        depth: 1,
+    }
+}
+
+fn is_bgra(format: wgpu::TextureFormat) -> bool {
+    match format {
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,2705 @@
+use std::cell::RefCell;
+use std::mem;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use iced_wgpu::{settings::Antialiasing, wgpu, Backend, Primitive, Renderer, Settings, Viewport};
+use iced_winit::{
+    mouse, program, window, winit, Color, Command, Debug, Event, Executor, Point, Program,
+    Rectangle, Size, Subscription, Vector,
+};
+
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use winit::{
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
+    event::{
+        DeviceEvent, ElementState, Event as WinitEvent, KeyboardInput, VirtualKeyCode, WindowEvent,
+    },
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    platform::desktop::EventLoopExtDesktop,
+    window::{CursorIcon, Fullscreen, Window, WindowBuilder},
+};
+
+#[cfg(target_os = "macos")]
+use cocoa::{
+    base::id,
+    foundation::{NSPoint, NSRect, NSSize, NSUInteger},
+};
+#[cfg(target_os = "macos")]
+use winit::platform::macos::WindowExtMacOS;
+
+use crate::accessibility;
+use crate::background::Background;
+use crate::capture;
+use crate::clipboard::{self, SystemClipboard};
+#[cfg(target_os = "macos")]
+use crate::cocoa_util;
+use crate::controls::Controls;
+use crate::damage::{DamageTracker, Redraw};
+use crate::display_link::DisplayLink;
+use crate::file_drop::DropTarget;
+use crate::format;
+use crate::gpu;
+use crate::hosted::{Hosted, OnMessage, SharedCursorRequests};
+use crate::input::Input;
+use crate::magnify::{Magnification, MagnifyRecognizer};
+use crate::metal_layer;
+use crate::parent;
+use crate::resize::Resizes;
+use crate::runtime::{Runtime, ThreadPool};
+use crate::schedule;
+use crate::stats::FrameTimer;
+use crate::theme;
+#[cfg(feature = "json")]
+use crate::EventRecorder;
+use crate::{
+    linear_color, AccessibilityNode, CaptureError, ChildWindowBuilder, ClipboardProvider,
+    CursorRequest, EmbedError, FileDrop, Filter, FrameStats, GpuInfo, InitError, MessageSender,
+    MetalLayerOptions, ParentSurface, RedrawHandle, RenderSchedule, Theme,
+};
+
+/// The `NSWindowOcclusionStateVisible` flag of `NSWindow.occlusionState`.
+#[cfg(target_os = "macos")]
+const NS_WINDOW_OCCLUSION_STATE_VISIBLE: NSUInteger = 1 << 1;
+
+/// The format of the depth texture.
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// The logical size of a standalone window, unless the builder sets it.
+const STANDALONE_WIDTH: f32 = 500.0;
+const STANDALONE_HEIGHT: f32 = 400.0;
+
+/// How far apart in points two clicks can be to count as a double-click.
+const DOUBLE_CLICK_DISTANCE: f64 = 4.0;
+
+/// How many times in a row we recreate the swap chain when it fails to give us a frame.
+const MAX_SWAP_CHAIN_RETRIES: u32 = 3;
+
+/// How many frames in a row we recreate the swap chain when it doesn't match the window.
+const MAX_SUBOPTIMAL_RETRIES: u32 = 1;
+
+/// Draws custom content into the frame before iced.
+type PreRender = Box<dyn FnMut(&mut wgpu::Device, &mut wgpu::CommandEncoder, &wgpu::TextureView)>;
+
+/// Decides whether a winit event reaches iced.
+type EventFilter = Box<dyn FnMut(&WindowEvent<'_>) -> Filter>;
+
+/// A [`ChildWindow`] hosting the demo [`Controls`].
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+/// [`Controls`]: controls/struct.Controls.html
+pub type DefaultChildWindow = ChildWindow<Controls>;
+
+/// An iced GUI rendered into a winit view that is embedded into a parent window.
+///
+/// The GUI is the iced `program` of type `P`.
+///
+/// Dropping it detaches the child view from the parent and releases the GPU resources.
+pub struct ChildWindow<P: 'static + Program<Renderer = Renderer>> {
+    // the fields are dropped in this order: the GPU resources before the device they belong
+    // to, and the surface before the window it's created for
+    state: program::State<Hosted<P>>,
+    renderer: Renderer,
+    pre_render: Option<PreRender>,
+    background: Option<Background>,
+    damage: Option<DamageTracker>,
+    depth: Option<(wgpu::Texture, wgpu::TextureView)>,
+    // released while the window is detached
+    swap_chain: Option<wgpu::SwapChain>,
+    swap_chain_size: PhysicalSize<u32>,
+    surface: wgpu::Surface,
+    queue: wgpu::Queue,
+    device: wgpu::Device,
+    clipboard: Box<dyn ClipboardProvider>,
+    window: Window,
+    embedded: bool,
+    in_host_view: bool,
+    detached: bool,
+    always_on_top: bool,
+    windowed_frame: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    gpu_info: GpuInfo,
+    on_gpu_reset: Option<Box<dyn FnMut(&GpuInfo)>>,
+    power_preference: wgpu::PowerPreference,
+    backends: wgpu::BackendBit,
+    anisotropic_filtering: bool,
+    limits: wgpu::Limits,
+    sample_count: u32,
+    default_font: Option<&'static [u8]>,
+    transparent: bool,
+    hdr: bool,
+    force_srgb: Option<bool>,
+    metal_layer: MetalLayerOptions,
+    format: wgpu::TextureFormat,
+    present_mode: wgpu::PresentMode,
+    on_message: OnMessage<P::Message>,
+    runtime: Option<Runtime<P::Message>>,
+    on_command: Option<Box<dyn FnMut(Command<P::Message>)>>,
+    subscription: Option<fn(&P) -> Subscription<P::Message>>,
+    cursor_requests: SharedCursorRequests<P::Message>,
+    cursor_icon: CursorIcon,
+    cursor_visible: bool,
+    cursor_grab: bool,
+    // in the logical units of the program
+    cursor_position: LogicalPosition<f64>,
+    last_press: Option<(Instant, LogicalPosition<f64>)>,
+    viewport: Viewport,
+    scale_factor: f64,
+    scale_factor_override: Option<f64>,
+    clear_color: wgpu::Color,
+    background_color: Option<fn(&P) -> Color>,
+    accessibility: Option<fn(&P) -> Vec<AccessibilityNode>>,
+    ime_position: Option<fn(&P) -> Option<Point>>,
+    on_file_drop: Option<fn(FileDrop) -> Option<P::Message>>,
+    on_close_requested: Option<Box<dyn FnMut() -> bool>>,
+    drop_target: Option<DropTarget>,
+    on_magnify: Option<fn(f32, Point) -> Option<P::Message>>,
+    magnify_recognizer: Option<MagnifyRecognizer>,
+    theme: Theme,
+    theme_override: Option<Theme>,
+    theme_message: Option<fn(Theme) -> P::Message>,
+    tab_message: Option<fn(bool) -> P::Message>,
+    key_message: Option<fn(VirtualKeyCode) -> Option<P::Message>>,
+    event_filter: Option<EventFilter>,
+    #[cfg(feature = "json")]
+    event_recorder: Option<EventRecorder>,
+    input: Input,
+    debug: Debug,
+    debug_overlay: bool,
+    debug_hotkey: Option<VirtualKeyCode>,
+    suspended: bool,
+    visible: bool,
+    occluded: bool,
+    swap_chain_retries: u32,
+    suboptimal_retries: u32,
+    min_size: Option<Size>,
+    max_size: Option<Size>,
+    render_schedule: RenderSchedule,
+    display_link: Option<DisplayLink>,
+    max_fps: Option<u32>,
+    last_frame: Instant,
+    tick: Option<(Duration, fn(Instant) -> P::Message)>,
+    last_tick: Instant,
+    frame_timer: FrameTimer,
+    dirty: bool,
+    resizes: Resizes,
+    redraw_requested: Arc<AtomicBool>,
+}
+
+impl<P: 'static + Program<Renderer = Renderer>> ChildWindow<P> {
+    /// Creates the child window running the `program`, sized to fill the `parent`, and attaches
+    /// it to the `parent`.
+    ///
+    /// Use [`ChildWindowBuilder`] to configure the window.
+    ///
+    /// [`ChildWindowBuilder`]: struct.ChildWindowBuilder.html
+    pub fn new<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        parent: &dyn ParentSurface,
+        program: P,
+    ) -> Result<Self, InitError> {
+        ChildWindowBuilder::new(program).build(event_loop, parent)
+    }
+
+    /// Creates the window running the `program` as a decorated top-level window of its own,
+    /// e.g. to iterate on the GUI without a host, or when the host has no valid parent.
+    ///
+    /// It renders and handles the events like an embedded one, and it can still be embedded
+    /// with [`embed_into`].
+    ///
+    /// [`embed_into`]: #method.embed_into
+    pub fn standalone<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        program: P,
+    ) -> Result<Self, InitError> {
+        ChildWindowBuilder::new(program).build_standalone(event_loop)
+    }
+
+    /// Creates the window and attaches it to the `parent`, or leaves it top-level without one.
+    pub(crate) fn build<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        parent: Option<&dyn ParentSurface>,
+        builder: ChildWindowBuilder<P>,
+    ) -> Result<Self, InitError> {
+        let span = tracing::info_span!("init");
+        let _enter = span.enter();
+
+        let frame = clamp_size(
+            builder
+                .frame
+                .or_else(|| parent.map(|parent| parent.frame()))
+                .unwrap_or_else(|| Size::new(STANDALONE_WIDTH, STANDALONE_HEIGHT)),
+            builder.min_size,
+            builder.max_size,
+        );
+
+        // Initialize winit
+        let mut window_builder = WindowBuilder::new()
+            // .with_activation_policy(ActivationPolicy::Prohibited)
+            .with_inner_size(LogicalSize::new(frame.width, frame.height))
+            .with_visible(true)
+            .with_transparent(builder.transparent);
+
+        if let Some(min_size) = builder.min_size {
+            window_builder = window_builder
+                .with_min_inner_size(LogicalSize::new(min_size.width, min_size.height));
+        }
+
+        if let Some(max_size) = builder.max_size {
+            window_builder = window_builder
+                .with_max_inner_size(LogicalSize::new(max_size.width, max_size.height));
+        }
+
+        let window = window_builder.build(event_loop)?;
+
+        // a top-level window is constrained by winit already
+        if let Some(parent) = parent {
+            parent.attach_child(window.raw_window_handle())?;
+
+            // we resize the view ourselves, the parent has to be constrained for the user
+            #[cfg(target_os = "macos")]
+            unsafe {
+                if let Some(parent_window) = cocoa_util::view_window(window.ns_view() as id) {
+                    if let Some(min_size) = builder.min_size {
+                        cocoa_util::set_content_min_size(
+                            parent_window,
+                            NSSize::new(min_size.width as f64, min_size.height as f64),
+                        );
+                    }
+
+                    if let Some(max_size) = builder.max_size {
+                        cocoa_util::set_content_max_size(
+                            parent_window,
+                            NSSize::new(max_size.width as f64, max_size.height as f64),
+                        );
+                    }
+                }
+            }
+        }
+
+        if builder.claim_tab {
+            claim_tab(&window);
+        }
+
+        if builder.always_on_top {
+            match parent {
+                Some(_) => set_parent_always_on_top(&window, true),
+                None => window.set_always_on_top(true),
+            }
+        }
+
+        // the pasteboard on macOS, text inputs paste from it, iced doesn't support copying yet
+        let clipboard: Box<dyn ClipboardProvider> = Box::new(SystemClipboard::new(&window));
+
+        let scale_factor = render_scale_factor(&window);
+        let physical_size = physical_size(&window, scale_factor);
+        // e.g. the parent is collapsed, we render nothing until it gets a size
+        let suspended = is_zero(physical_size);
+        let mut resizes = Resizes::default();
+
+        // the swap chain is created with a non-zero size then
+        if suspended {
+            resizes.invalidate();
+        }
+        let viewport = Viewport::with_physical_size(
+            Size::new(physical_size.width, physical_size.height),
+            scale_factor,
+        );
+
+        // Initialize wgpu
+        let surface = create_surface(&window, builder.transparent);
+        // on the layer wgpu has just backed the view with
+        set_contents_scale(&window, scale_factor);
+
+        let supplied_device = builder.device.is_some();
+        let (mut device, queue, adapter_info) = match builder.device {
+            Some(device) => device,
+            None => {
+                let (adapter, device, queue) = gpu::request(
+                    builder.power_preference,
+                    builder.backends,
+                    builder.anisotropic_filtering,
+                    &builder.limits,
+                    Some(&surface),
+                )?;
+
+                (device, queue, adapter.get_info())
+            }
+        };
+
+        let gpu_info = GpuInfo::from(adapter_info);
+        let format = format::select(gpu_info.backend, builder.hdr, builder.force_srgb);
+
+        // our own adapter is compatible with the surface, the one of the host may not be
+        if supplied_device && !format::is_supported(gpu_info.backend, format) {
+            return Err(InitError::UnsupportedFormat {
+                format,
+                backend: gpu_info.backend,
+            });
+        }
+
+        set_extended_range(&window, !format::is_srgb(format));
+        let present_mode = builder.present_mode;
+        // the swap chain can't have zero size, it's recreated when we are resumed anyway
+        let swap_chain_size =
+            PhysicalSize::new(physical_size.width.max(1), physical_size.height.max(1));
+        let swap_chain =
+            create_swap_chain(&device, &surface, format, present_mode, swap_chain_size);
+        metal_layer::configure(&window, &builder.metal_layer);
+        let depth = if builder.depth_buffer {
+            Some(create_depth(&device, swap_chain_size))
+        } else {
+            None
+        };
+        let damage = if builder.damage_tracking {
+            Some(DamageTracker::new(&device, format))
+        } else {
+            None
+        };
+
+        // Initialize iced
+        let mut debug = Debug::new();
+        let settings = renderer_settings(format, builder.sample_count, builder.default_font);
+        let mut renderer = Renderer::new(Backend::new(&mut device, settings));
+
+        let runtime = match builder.runtime {
+            Some(sender) => Some(Runtime::new(
+                ThreadPool::new().map_err(InitError::Executor)?,
+                sender,
+            )),
+            None => None,
+        };
+
+        let on_message = Rc::new(RefCell::new(None));
+        let cursor_requests = Rc::new(RefCell::new(Default::default()));
+        let state = program::State::new(
+            Hosted::new(builder.program, on_message.clone(), cursor_requests.clone()),
+            viewport.logical_size(),
+            &mut renderer,
+            &mut debug,
+        );
+
+        Ok(ChildWindow {
+            window,
+            embedded: parent.is_some(),
+            in_host_view: false,
+            detached: false,
+            always_on_top: builder.always_on_top,
+            windowed_frame: None,
+            clipboard,
+            surface,
+            device,
+            queue,
+            swap_chain: Some(swap_chain),
+            swap_chain_size,
+            depth,
+            gpu_info,
+            on_gpu_reset: None,
+            power_preference: builder.power_preference,
+            backends: builder.backends,
+            anisotropic_filtering: builder.anisotropic_filtering,
+            limits: builder.limits,
+            sample_count: builder.sample_count,
+            default_font: builder.default_font,
+            transparent: builder.transparent,
+            hdr: builder.hdr,
+            force_srgb: builder.force_srgb,
+            metal_layer: builder.metal_layer,
+            format,
+            present_mode,
+            renderer,
+            state,
+            on_message,
+            runtime,
+            on_command: None,
+            subscription: None,
+            cursor_requests,
+            cursor_icon: CursorIcon::default(),
+            cursor_visible: true,
+            cursor_grab: false,
+            cursor_position: LogicalPosition::new(0.0, 0.0),
+            last_press: None,
+            pre_render: None,
+            background: None,
+            damage,
+            viewport,
+            scale_factor,
+            scale_factor_override: None,
+            clear_color: if builder.transparent {
+                wgpu::Color::TRANSPARENT
+            } else {
+                builder.clear_color
+            },
+            background_color: None,
+            accessibility: None,
+            ime_position: None,
+            on_file_drop: None,
+            on_close_requested: None,
+            drop_target: create_drop_target(&window),
+            on_magnify: None,
+            magnify_recognizer: create_magnify_recognizer(&window),
+            theme: theme::system_theme(),
+            theme_override: None,
+            theme_message: None,
+            tab_message: None,
+            key_message: None,
+            event_filter: None,
+            #[cfg(feature = "json")]
+            event_recorder: None,
+            input: Input::default(),
+            debug,
+            debug_overlay: false,
+            debug_hotkey: Some(VirtualKeyCode::F12),
+            suspended,
+            visible: true,
+            occluded: false,
+            swap_chain_retries: 0,
+            suboptimal_retries: 0,
+            min_size: builder.min_size,
+            max_size: builder.max_size,
+            render_schedule: builder.render_schedule,
+            display_link: start_display_link(builder.render_schedule),
+            max_fps: None,
+            last_frame: Instant::now(),
+            tick: None,
+            last_tick: Instant::now(),
+            frame_timer: FrameTimer::default(),
+            // the first frame has to be drawn
+            dirty: true,
+            resizes,
+            redraw_requested: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// The underlying winit window.
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Embeds the child window into another native `parent` window.
+    ///
+    /// `parent` must be a valid `NSWindow` on macOS or `HWND` on Windows. A [`standalone`]
+    /// window stays top-level if it fails.
+    ///
+    /// [`standalone`]: #method.standalone
+    pub fn embed_into(&mut self, parent: RawWindowHandle) -> Result<(), EmbedError> {
+        parent::from_raw(parent)?.attach_child(self.window.raw_window_handle())?;
+        self.embedded = true;
+
+        if self.always_on_top {
+            set_parent_always_on_top(&self.window, true);
+        }
+
+        Ok(())
+    }
+
+    /// Embeds the child window into the `parent_view` at the `frame` in its bounds, e.g. into the
+    /// view a VST3 or AUv2 host hands to the editor when it's opened.
+    ///
+    /// No window is created for it, the child view is added to the `parent_view` directly and
+    /// resized to the `frame`. Dropping the child window removes the view again.
+    ///
+    /// # Safety
+    ///
+    /// `parent_view` must be a valid `NSView`, which outlives the child window, and it must be
+    /// called on the main thread.
+    #[cfg(target_os = "macos")]
+    pub unsafe fn embed_in_nsview(&mut self, parent_view: id, frame: NSRect) {
+        cocoa_util::attach_subview_at(parent_view, self.window.ns_view() as id, frame);
+        self.embedded = true;
+        self.in_host_view = true;
+
+        self.resize(Size::new(frame.size.width as f32, frame.size.height as f32));
+    }
+
+    /// Keeps the window above the other windows, or lets it go behind them again.
+    ///
+    /// A top-level window uses winit's `set_always_on_top`. A window embedded into a parent
+    /// window raises that window instead, to the floating level on macOS and topmost on Windows.
+    /// It's a no-op in the view of a host, see [`embed_in_nsview`], whose window belongs to the
+    /// host.
+    ///
+    /// [`embed_in_nsview`]: #method.embed_in_nsview
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        self.always_on_top = always_on_top;
+
+        if !self.embedded {
+            self.window.set_always_on_top(always_on_top);
+        } else if !self.in_host_view {
+            set_parent_always_on_top(&self.window, always_on_top);
+        }
+    }
+
+    /// Makes a [`standalone`] window fill the monitor it's on, borderless, or restores its
+    /// previous frame.
+    ///
+    /// winit reports the new size with `Resized`, which resizes the swap chain and the viewport
+    /// like any resize. An embedded window can't go fullscreen, it stays in its parent.
+    ///
+    /// [`standalone`]: #method.standalone
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        if self.embedded {
+            tracing::warn!("An embedded window can't go fullscreen");
+            return;
+        }
+
+        if fullscreen == self.is_fullscreen() {
+            return;
+        }
+
+        if fullscreen {
+            // winit doesn't restore the frame on every platform
+            self.windowed_frame = self
+                .window
+                .outer_position()
+                .ok()
+                .map(|position| (position, self.window.inner_size()));
+            self.window
+                .set_fullscreen(Some(Fullscreen::Borderless(self.window.current_monitor())));
+        } else {
+            self.window.set_fullscreen(None);
+
+            if let Some((position, size)) = self.windowed_frame.take() {
+                self.window.set_outer_position(position);
+                self.window.set_inner_size(size);
+            }
+        }
+    }
+
+    /// Whether the window is fullscreen, see [`set_fullscreen`].
+    ///
+    /// [`set_fullscreen`]: #method.set_fullscreen
+    pub fn is_fullscreen(&self) -> bool {
+        self.window.fullscreen().is_some()
+    }
+
+    /// Detaches the view of the window from its parent and stops rendering, e.g. when the host
+    /// closes the editor, keeping the GPU device and the state of the program for [`resume`].
+    ///
+    /// The swap chain is released and the display link stopped. The events and messages still
+    /// reach the program meanwhile, so it keeps up with the host.
+    ///
+    /// [`resume`]: #method.resume
+    #[cfg(target_os = "macos")]
+    pub fn suspend(&mut self) {
+        if self.detached {
+            return;
+        }
+
+        // winit retains the view, so it survives being removed
+        unsafe { cocoa_util::detach_view(self.window.ns_view() as id) };
+        self.detached = true;
+        self.swap_chain = None;
+        self.display_link = None;
+    }
+
+    /// Attaches the view of a [`suspend`]ed window to the `parent` view at the `frame` in its
+    /// bounds, like [`embed_in_nsview`], and renders it again.
+    ///
+    /// The swap chain is recreated for the size and the scale of the new parent on the next
+    /// frame, which repaints the program as it was.
+    ///
+    /// # Safety
+    ///
+    /// `parent` must be a valid `NSView`, which outlives the child window, and it must be called
+    /// on the main thread.
+    ///
+    /// [`suspend`]: #method.suspend
+    /// [`embed_in_nsview`]: #method.embed_in_nsview
+    #[cfg(target_os = "macos")]
+    pub unsafe fn resume(&mut self, parent: id, frame: NSRect) {
+        self.embed_in_nsview(parent, frame);
+
+        if !self.detached {
+            return;
+        }
+
+        self.detached = false;
+        self.display_link = start_display_link(self.render_schedule);
+        self.update_scale_factor();
+        self.resizes.invalidate();
+        self.dirty = true;
+    }
+
+    /// The view of the depth texture, if the window was built with [`depth_buffer`].
+    ///
+    /// It has the size of the frame and is cleared to 1 with it, so passes drawing custom
+    /// content into the frame can use it as their depth attachment.
+    ///
+    /// [`depth_buffer`]: struct.ChildWindowBuilder.html#method.depth_buffer
+    pub fn depth_view(&self) -> Option<&wgpu::TextureView> {
+        self.depth.as_ref().map(|(_, view)| view)
+    }
+
+    /// The adapter the window renders with.
+    pub fn gpu_info(&self) -> &GpuInfo {
+        &self.gpu_info
+    }
+
+    /// The timings of the last frames.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_timer.stats()
+    }
+
+    /// The viewport the program is laid out and rendered in, in physical pixels with the scale
+    /// factor of the program.
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    /// The size of the program in its logical units, e.g. to lay out an overlay of the host over
+    /// the window.
+    pub fn logical_size(&self) -> Size {
+        self.viewport.logical_size()
+    }
+
+    /// The pixels per logical unit the program is laid out in, the override of
+    /// [`set_scale_factor_override`] if there's one.
+    ///
+    /// [`set_scale_factor_override`]: #method.set_scale_factor_override
+    pub fn scale_factor(&self) -> f64 {
+        self.ui_scale_factor()
+    }
+
+    /// Lays out and renders the program with the `scale_factor` instead of the one of the
+    /// display, e.g. 1 for a host scaling the GUI itself, or reverts to the display's with
+    /// `None`.
+    ///
+    /// The window is still rendered in the pixels of the display. The program is laid out in
+    /// `scale_factor` pixels per logical unit instead, and the positions of the events are
+    /// converted with it, so the program hit-tests what it has drawn.
+    pub fn set_scale_factor_override(&mut self, scale_factor: Option<f64>) {
+        self.scale_factor_override = scale_factor;
+        self.resize_viewport(physical_size(&self.window, self.scale_factor));
+    }
+
+    /// The pixels per logical unit of the program.
+    fn ui_scale_factor(&self) -> f64 {
+        self.scale_factor_override.unwrap_or(self.scale_factor)
+    }
+
+    /// The logical units of the program per point, 1 unless the scale factor is overridden.
+    fn units_per_point(&self) -> f64 {
+        self.scale_factor / self.ui_scale_factor()
+    }
+
+    /// The scale factor converting the physical positions winit reports to logical units of the
+    /// program.
+    fn event_scale_factor(&self) -> f64 {
+        self.window.scale_factor() / self.units_per_point()
+    }
+
+    /// Shows or hides the iced debug overlay, a performance HUD in the corner of the window.
+    /// It's hidden by default.
+    ///
+    /// iced measures only when it's built with its `debug` feature, otherwise the overlay is
+    /// empty, so the measurements cost nothing in release builds.
+    pub fn set_debug_overlay(&mut self, debug_overlay: bool) {
+        if debug_overlay != self.debug_overlay {
+            self.debug.toggle();
+            self.debug_overlay = debug_overlay;
+            self.dirty = true;
+        }
+    }
+
+    /// Makes the program paste from the `clipboard` instead of the system clipboard, e.g. to
+    /// route it through the host.
+    pub fn set_clipboard(&mut self, clipboard: impl ClipboardProvider + 'static) {
+        self.clipboard = Box::new(clipboard);
+    }
+
+    /// The clipboard the program pastes from, e.g. to copy what it has selected.
+    pub fn clipboard(&mut self) -> &mut dyn ClipboardProvider {
+        &mut *self.clipboard
+    }
+
+    /// Sets the key toggling the debug overlay, `None` disables it. F12 by default.
+    ///
+    /// The key presses toggling it aren't passed to the program.
+    pub fn set_debug_hotkey(&mut self, debug_hotkey: Option<VirtualKeyCode>) {
+        self.debug_hotkey = debug_hotkey;
+    }
+
+    /// The format of the swap chain.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// The hosted program.
+    pub fn program(&self) -> &P {
+        &self.state.program().program
+    }
+
+    /// Sets the color the frame is cleared with before iced draws on top. Transparent by default.
+    ///
+    /// The color is linear, convert an sRGB color with [`linear_color`]. The alpha takes effect
+    /// only if the surface format supports it.
+    ///
+    /// [`linear_color`]: fn.linear_color.html
+    pub fn set_clear_color(&mut self, color: wgpu::Color) {
+        self.clear_color = color;
+    }
+
+    /// Makes the clear color follow the sRGB color returned by `background_color` for the
+    /// program, e.g. `Controls::background_color`.
+    ///
+    /// The color is read again after each update of the program, overriding the color set with
+    /// [`set_clear_color`].
+    ///
+    /// [`set_clear_color`]: #method.set_clear_color
+    pub fn sync_clear_color(&mut self, background_color: fn(&P) -> Color) {
+        self.clear_color = linear_color(background_color(self.program()));
+        self.background_color = Some(background_color);
+    }
+
+    /// Sets the present mode of the swap chain, e.g. `Fifo` to sync to the display and save
+    /// power, or `Immediate` for the lowest latency, and recreates the swap chain.
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
+        self.present_mode = present_mode;
+        self.recreate_swap_chain();
+        self.dirty = true;
+    }
+
+    /// Sets when the window is redrawn.
+    pub fn set_render_schedule(&mut self, render_schedule: RenderSchedule) {
+        // a detached window starts its display link when it's resumed
+        if render_schedule != self.render_schedule && !self.detached {
+            // stop the old display link before starting a new one
+            self.display_link = None;
+            self.display_link = start_display_link(render_schedule);
+        }
+
+        self.render_schedule = render_schedule;
+    }
+
+    /// Caps the frame rate of any [`RenderSchedule`] to `fps`, e.g. 30 for an always-on
+    /// visualizer to save power, or removes the cap with `None`.
+    ///
+    /// The frames the schedule requests sooner than `1 / fps` after the last one are held back
+    /// until the interval has elapsed. Only the dirty frames are rendered with `OnDemand`, it's
+    /// never rendered more often than it's needed.
+    ///
+    /// [`RenderSchedule`]: enum.RenderSchedule.html
+    pub fn set_max_fps(&mut self, fps: Option<u32>) {
+        self.max_fps = fps;
+    }
+
+    /// Whether a frame rendered now would exceed the maximum frame rate.
+    fn is_capped(&self) -> bool {
+        self.max_fps.map_or(false, |fps| {
+            self.last_frame.elapsed() < schedule::frame_interval(fps)
+        })
+    }
+
+    /// The control flow that lets the event loop wait between the frames of the
+    /// [`RenderSchedule`].
+    ///
+    /// It's useful only if we own the event loop. In a plugin the host drives the loop.
+    ///
+    /// [`RenderSchedule`]: enum.RenderSchedule.html
+    pub fn control_flow(&self) -> ControlFlow {
+        let control_flow = match self.render_schedule {
+            // nothing is rendered until the window is resumed or shown
+            _ if self.detached || !self.visible => ControlFlow::Wait,
+            RenderSchedule::Continuous => ControlFlow::Poll,
+            RenderSchedule::OnDemand => ControlFlow::Wait,
+            RenderSchedule::Throttled { fps } => {
+                ControlFlow::WaitUntil(self.last_frame + schedule::frame_interval(fps))
+            }
+            // the display link wakes the loop
+            RenderSchedule::DisplayLink if self.display_link.is_some() => ControlFlow::Wait,
+            RenderSchedule::DisplayLink => ControlFlow::WaitUntil(
+                self.last_frame + schedule::frame_interval(schedule::FALLBACK_FPS),
+            ),
+        };
+
+        let control_flow = match self.max_fps {
+            Some(fps) => schedule::cap(
+                control_flow,
+                self.last_frame,
+                fps,
+                self.dirty && self.can_render(),
+            ),
+            None => control_flow,
+        };
+
+        // wake up for the next tick too, if it's sooner
+        match (control_flow, self.tick) {
+            (ControlFlow::Wait, Some((interval, _))) => {
+                ControlFlow::WaitUntil(self.last_tick + interval)
+            }
+            (ControlFlow::WaitUntil(instant), Some((interval, _))) => {
+                ControlFlow::WaitUntil(instant.min(self.last_tick + interval))
+            }
+            _ => control_flow,
+        }
+    }
+
+    /// Queues the message `tick` returns for the current time every `interval`, e.g. to animate
+    /// a VU meter or a metronome.
+    ///
+    /// The ticks are driven by [`update`] instead of a thread, so they arrive right before the
+    /// frames: with an interval shorter than a frame there is a tick per frame. The
+    /// [`control_flow`] wakes the event loop for them, until they're stopped with
+    /// [`stop_tick`].
+    ///
+    /// [`update`]: #method.update
+    /// [`control_flow`]: #method.control_flow
+    /// [`stop_tick`]: #method.stop_tick
+    pub fn set_tick(&mut self, interval: Duration, tick: fn(Instant) -> P::Message) {
+        self.tick = Some((interval, tick));
+        self.last_tick = Instant::now();
+    }
+
+    /// Stops the ticks set with [`set_tick`], so the event loop can wait for the events again.
+    ///
+    /// [`set_tick`]: #method.set_tick
+    pub fn stop_tick(&mut self) {
+        self.tick = None;
+    }
+
+    /// Resizes the child window to the logical `new_size`.
+    ///
+    /// Call it when the parent changes its size, because the child isn't notified about it.
+    pub fn resize(&mut self, new_size: Size) {
+        let new_size = clamp_size(new_size, self.min_size, self.max_size);
+        let logical_size = LogicalSize::new(new_size.width as f64, new_size.height as f64);
+
+        // winit measures the window by the frame of its view, which we have moved to the parent
+        #[cfg(target_os = "macos")]
+        {
+            // the origin stays, it may be offset in a view of the host
+            if self.embedded {
+                unsafe {
+                    cocoa_util::set_view_size(
+                        self.window.ns_view() as id,
+                        NSSize::new(logical_size.width, logical_size.height),
+                    )
+                };
+            } else {
+                self.window.set_inner_size(logical_size);
+            }
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        self.window.set_inner_size(logical_size);
+
+        self.resize_viewport(logical_size.to_physical(self.scale_factor));
+    }
+
+    /// Moves and resizes the view of an embedded window to the `frame` in the coordinates of its
+    /// superview, e.g. when the host rearranges its panels while the parent keeps its size.
+    ///
+    /// The size is clamped to the [`min_size`] and the [`max_size`]. winit measures the cursor
+    /// from the view, so the program hit-tests the new frame from the next cursor move. A
+    /// top-level window is only resized, its view is the content of the window.
+    ///
+    /// [`min_size`]: struct.ChildWindowBuilder.html#method.min_size
+    /// [`max_size`]: struct.ChildWindowBuilder.html#method.max_size
+    #[cfg(target_os = "macos")]
+    pub fn set_child_frame(&mut self, frame: NSRect) {
+        let size = Size::new(frame.size.width as f32, frame.size.height as f32);
+
+        if !self.embedded {
+            self.resize(size);
+            return;
+        }
+
+        let size = clamp_size(size, self.min_size, self.max_size);
+        let logical_size = LogicalSize::new(size.width as f64, size.height as f64);
+
+        unsafe {
+            cocoa_util::set_view_frame(
+                self.window.ns_view() as id,
+                NSRect::new(
+                    frame.origin,
+                    NSSize::new(logical_size.width, logical_size.height),
+                ),
+            )
+        };
+
+        self.resize_viewport(logical_size.to_physical(self.scale_factor));
+        // they're positioned in the window, which the view has moved in
+        self.update_ime_position();
+    }
+
+    /// A handle requesting redraws from other threads, see [`RedrawHandle`].
+    ///
+    /// It wakes the event loop of the `sender` with the `wake_up` event, e.g. a variant of the
+    /// user events of the host that does nothing else. The loop only has to run an iteration
+    /// for it.
+    ///
+    /// [`RedrawHandle`]: struct.RedrawHandle.html
+    pub fn redraw_handle<T: 'static + Clone>(
+        &self,
+        sender: MessageSender<T>,
+        wake_up: T,
+    ) -> RedrawHandle<T> {
+        RedrawHandle::new(self.redraw_requested.clone(), sender, wake_up)
+    }
+
+    /// Makes the next [`update`] request a redraw, even if the GUI hasn't changed.
+    ///
+    /// [`update`]: #method.update
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Sets the callback invoked for each message the program handles, before handling it.
+    ///
+    /// The messages come both from the GUI and from [`queue_message`]. E.g. a plugin can map
+    /// slider changes to parameter automation here.
+    ///
+    /// [`queue_message`]: #method.queue_message
+    pub fn set_on_message(&mut self, on_message: impl FnMut(&P::Message) + 'static) {
+        *self.on_message.borrow_mut() = Some(Box::new(on_message));
+    }
+
+    /// Sets the callback receiving the `Command` of each update that handled messages, for a
+    /// window without a runtime, e.g. to run the commands on the executor of the host.
+    ///
+    /// The command batches the futures the program returned from `update` for the messages,
+    /// `Command::futures` splits it into them. Each of them produces a message, which the host
+    /// passes back with [`queue_message`]. The messages themselves are reported to the callback
+    /// of [`set_on_message`]. With a runtime, see [`with_runtime`], the commands are run by it
+    /// instead, otherwise they are dropped without a callback.
+    ///
+    /// [`queue_message`]: #method.queue_message
+    /// [`set_on_message`]: #method.set_on_message
+    /// [`with_runtime`]: struct.ChildWindowBuilder.html#method.with_runtime
+    pub fn set_on_command(&mut self, on_command: impl FnMut(Command<P::Message>) + 'static) {
+        self.on_command = Some(Box::new(on_command));
+    }
+
+    /// Subscribes the program to the `subscription` it returns, e.g. to a timer. It's refreshed
+    /// after each update that handled a message.
+    ///
+    /// It needs a window built [`with_runtime`], which runs it. The messages it produces arrive
+    /// to the event loop like the ones of a [`MessageSender`].
+    ///
+    /// [`with_runtime`]: struct.ChildWindowBuilder.html#method.with_runtime
+    /// [`MessageSender`]: struct.MessageSender.html
+    pub fn set_subscription(&mut self, subscription: fn(&P) -> Subscription<P::Message>) {
+        self.subscription = Some(subscription);
+
+        if let Some(runtime) = &mut self.runtime {
+            runtime.track(subscription(&self.state.program().program));
+        }
+    }
+
+    /// Describes the controls of the program to the screen readers with the nodes
+    /// `accessibility` returns, e.g. their labels and values.
+    ///
+    /// They're published as the `NSAccessibility` children of the view on macOS, again after
+    /// each update that handled a message. Elsewhere they aren't published yet.
+    pub fn set_accessibility(&mut self, accessibility: fn(&P) -> Vec<AccessibilityNode>) {
+        self.accessibility = Some(accessibility);
+        self.publish_accessibility();
+    }
+
+    fn publish_accessibility(&self) {
+        if let Some(accessibility) = self.accessibility {
+            accessibility::publish(
+                &self.window,
+                self.viewport.logical_size(),
+                1.0 / self.units_per_point(),
+                &accessibility(self.program()),
+            );
+        }
+    }
+
+    /// Places the candidate window of the input method, e.g. for Japanese or Chinese, at the
+    /// logical position in the window `ime_position` returns, usually below the caret of the
+    /// focused text input. It's kept where it is on `None`.
+    ///
+    /// iced doesn't report the caret of its text inputs, so the program has to track it. winit
+    /// handles the composition in the view and sends the committed text as
+    /// `ReceivedCharacter`s, which reach the text inputs like typed characters. It doesn't
+    /// report the marked text, so the text being composed is only shown by the candidate window.
+    ///
+    /// The position is queried again after each update that handled a message.
+    pub fn set_ime_position(&mut self, ime_position: fn(&P) -> Option<Point>) {
+        self.ime_position = Some(ime_position);
+        self.update_ime_position();
+    }
+
+    fn update_ime_position(&self) {
+        if let Some(position) = self
+            .ime_position
+            .and_then(|ime_position| ime_position(self.program()))
+        {
+            let points_per_unit = 1.0 / self.units_per_point() as f32;
+
+            set_ime_position(
+                &self.window,
+                Point::new(position.x * points_per_unit, position.y * points_per_unit),
+            );
+        }
+    }
+
+    /// Sets the callback that draws into the frame after it's cleared and before iced draws on
+    /// top, e.g. a waveform behind the controls.
+    ///
+    /// Its passes must load the frame, not clear it. iced loads it too, so the content stays
+    /// under the GUI.
+    pub fn set_pre_render(
+        &mut self,
+        pre_render: impl FnMut(&mut wgpu::Device, &mut wgpu::CommandEncoder, &wgpu::TextureView)
+            + 'static,
+    ) {
+        self.pre_render = Some(Box::new(pre_render));
+        self.dirty = true;
+    }
+
+    /// Draws the `texture` the host renders, e.g. a shared spectrogram, into each frame before
+    /// the [`set_pre_render`] callback and iced draw on top, or stops drawing it with `None`.
+    ///
+    /// It's the view and the size in pixels of a texture created with the device of the window
+    /// and the `SAMPLED` usage. It's scaled to fit the window with its aspect ratio, the bars of
+    /// a mismatched one keep the clear color. The host can keep rendering into the texture, the
+    /// window draws what it holds when a frame is rendered, see [`mark_dirty`].
+    ///
+    /// It's dropped when the GPU is reinitialized, see [`set_on_gpu_reset`].
+    ///
+    /// [`set_pre_render`]: #method.set_pre_render
+    /// [`mark_dirty`]: #method.mark_dirty
+    /// [`set_on_gpu_reset`]: #method.set_on_gpu_reset
+    pub fn set_background_texture(&mut self, texture: Option<(wgpu::TextureView, Size<u32>)>) {
+        match texture {
+            Some((view, size)) => {
+                let (device, format) = (&self.device, self.format);
+
+                self.background
+                    .get_or_insert_with(|| Background::new(device, format))
+                    .set_texture(device, &view, size);
+            }
+            None => self.background = None,
+        }
+
+        if let Some(damage) = &mut self.damage {
+            damage.invalidate();
+        }
+
+        self.dirty = true;
+    }
+
+    /// Queues the `message` to the program, e.g. a parameter change from the host. It's handled
+    /// on the next [`update`], which requests a redraw.
+    ///
+    /// The queued messages are handled in their order, after the messages the GUI produces from
+    /// the events queued for the same update, so the host has the last word on a parameter that
+    /// both change at once.
+    ///
+    /// [`update`]: #method.update
+    pub fn queue_message(&mut self, message: P::Message) {
+        self.state.queue_message(message);
+        self.dirty = true;
+    }
+
+    /// Whether rendering is suspended, because the window has zero size, e.g. when it's
+    /// minimized.
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// The current theme.
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    /// Overrides the theme. `None` follows the appearance of the system, which is the default.
+    pub fn set_theme(&mut self, theme: Option<Theme>) {
+        self.theme_override = theme;
+        self.update_theme();
+    }
+
+    /// Makes the program restyle to the theme with the messages `theme_message` returns, e.g.
+    /// `controls::Message::ThemeChanged`.
+    ///
+    /// The message for the current theme is queued right away, and then on each change, e.g.
+    /// when the user toggles the dark mode.
+    pub fn set_theme_message(&mut self, theme_message: fn(Theme) -> P::Message) {
+        self.theme_message = Some(theme_message);
+        self.queue_message(theme_message(self.theme));
+    }
+
+    /// Moves the focus of the program with the messages `tab_message` returns when Tab is
+    /// pressed, with `true` for Shift+Tab, e.g. `controls::Message::FocusMoved`.
+    ///
+    /// iced doesn't move the focus between its widgets on its own, so the program has to track
+    /// it. The key events still reach iced as well. If the host takes Tab before the window, see
+    /// [`ChildWindowBuilder::claim_tab`].
+    ///
+    /// [`ChildWindowBuilder::claim_tab`]: struct.ChildWindowBuilder.html#method.claim_tab
+    pub fn set_tab_message(&mut self, tab_message: fn(bool) -> P::Message) {
+        self.tab_message = Some(tab_message);
+    }
+
+    /// Queues the message `key_message` returns for a pressed key, if any, e.g.
+    /// `Controls::key_message` moving the focused slider with the arrow keys.
+    ///
+    /// It lets the program handle the keys for the widgets it focuses with
+    /// [`set_tab_message`], which iced doesn't deliver them to. The key events still reach iced
+    /// as well.
+    ///
+    /// [`set_tab_message`]: #method.set_tab_message
+    pub fn set_key_message(&mut self, key_message: fn(VirtualKeyCode) -> Option<P::Message>) {
+        self.key_message = Some(key_message);
+    }
+
+    fn update_theme(&mut self) {
+        let theme = self.theme_override.unwrap_or_else(theme::system_theme);
+
+        if theme == self.theme {
+            return;
+        }
+
+        self.theme = theme;
+
+        if let Some(theme_message) = self.theme_message {
+            self.queue_message(theme_message(theme));
+        }
+    }
+
+    /// Shows or hides the mouse cursor while it's over the window, e.g. during a relative drag.
+    ///
+    /// It works while the window is embedded too: the cursor is hidden only over the view, so
+    /// it shows again over the parent.
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.cursor_visible = visible;
+        self.window.set_cursor_visible(visible);
+        self.invalidate_cursor();
+    }
+
+    /// Grabs the mouse cursor, so it can't leave the window, or releases it, e.g. for a knob that
+    /// is dragged infinitely.
+    ///
+    /// The grab is released while the window isn't focused and taken again once it is.
+    ///
+    /// macOS doesn't confine the cursor, it freezes it in place, so it's hidden meanwhile and the
+    /// program gets the cursor moved by the mouse deltas instead, which the host passes to
+    /// [`process_device_event`].
+    ///
+    /// [`process_device_event`]: #method.process_device_event
+    pub fn set_cursor_grab(&mut self, grab: bool) {
+        self.cursor_grab = grab;
+        self.grab_cursor(grab);
+    }
+
+    fn grab_cursor(&mut self, grab: bool) {
+        if let Err(error) = self.window.set_cursor_grab(grab) {
+            tracing::warn!("Failed to grab or release the cursor: {}", error);
+        }
+
+        // a frozen cursor would hang over the GUI, the visibility the host wants comes back after
+        #[cfg(target_os = "macos")]
+        {
+            self.window.set_cursor_visible(!grab && self.cursor_visible);
+            self.invalidate_cursor();
+        }
+    }
+
+    /// Moves the mouse cursor to the logical `position` in the window, where iced measures the
+    /// cursor, e.g. to center it on a knob mid-drag.
+    ///
+    /// The program sees the cursor there right away. On macOS the position is measured from the
+    /// view in the parent, so the title bar of a titled parent is accounted for, and a grabbed
+    /// cursor stays grabbed.
+    pub fn set_cursor_position(&mut self, position: Point) {
+        let units_per_point = self.units_per_point();
+        let logical_position = LogicalPosition::new(
+            position.x as f64 / units_per_point,
+            position.y as f64 / units_per_point,
+        );
+
+        #[cfg(target_os = "macos")]
+        let moved = unsafe {
+            cocoa_util::warp_cursor(
+                self.window.ns_view() as id,
+                NSPoint::new(logical_position.x, logical_position.y),
+            )
+        };
+
+        // winit converts the logical position with the scale factor of the window
+        #[cfg(not(target_os = "macos"))]
+        let moved = self.window.set_cursor_position(logical_position).is_ok();
+
+        if !moved {
+            tracing::warn!("Failed to move the cursor to {:?}", position);
+            return;
+        }
+
+        self.cursor_position = LogicalPosition::new(position.x as f64, position.y as f64);
+        self.queue_event(Event::Mouse(mouse::Event::CursorMoved {
+            x: position.x,
+            y: position.y,
+        }));
+    }
+
+    /// Lets the program change the cursor with the requests `cursor_request` maps its messages
+    /// to, e.g. to hide it while a knob is dragged.
+    ///
+    /// The requests are applied after the update in which the program handles the messages.
+    pub fn set_cursor_requests(
+        &mut self,
+        cursor_request: fn(&P::Message) -> Option<CursorRequest>,
+    ) {
+        self.cursor_requests.borrow_mut().request = Some(cursor_request);
+    }
+
+    fn apply_cursor_requests(&mut self) {
+        let requests = mem::take(&mut self.cursor_requests.borrow_mut().pending);
+
+        for request in requests {
+            match request {
+                CursorRequest::SetVisible(visible) => self.set_cursor_visible(visible),
+                CursorRequest::SetGrab(grab) => self.set_cursor_grab(grab),
+                CursorRequest::SetPosition(position) => self.set_cursor_position(position),
+            }
+        }
+    }
+
+    fn set_cursor_icon(&mut self, cursor_icon: CursorIcon) {
+        // it's set every frame, but needs updating only when the interaction changes
+        if cursor_icon == self.cursor_icon {
+            return;
+        }
+
+        self.cursor_icon = cursor_icon;
+        self.window.set_cursor_icon(cursor_icon);
+        self.invalidate_cursor();
+    }
+
+    fn invalidate_cursor(&self) {
+        #[cfg(target_os = "macos")]
+        unsafe {
+            cocoa_util::invalidate_cursor_rects(self.window.ns_view() as id)
+        };
+    }
+
+    /// Sets the title of the top-level window hosting the child window, e.g. when it's shown in
+    /// a titled parent on its own.
+    pub fn set_parent_title(&self, title: &str) {
+        set_parent_title(&self.window, title);
+    }
+
+    /// Makes the view of the window receive the keyboard events.
+    ///
+    /// It's done on click already, call it when the host should give the keyboard to the
+    /// program, e.g. when the editor is opened.
+    pub fn focus(&self) {
+        make_first_responder(&self.window);
+    }
+
+    /// Shows or hides the window, e.g. when the host hides the plugin editor without
+    /// destroying it. Nothing is rendered while it's hidden.
+    ///
+    /// An embedded window hides its view in the parent, the `hidden` property of the `NSView` on
+    /// macOS, a top-level one hides itself. Independently of it, rendering is paused while the
+    /// window is occluded.
+    pub fn set_visible(&mut self, visible: bool) {
+        if visible && !self.visible {
+            // repaint the stale content
+            self.dirty = true;
+        }
+
+        self.visible = visible;
+        set_window_visible(&self.window, self.embedded, visible);
+    }
+
+    /// Handles a winit event addressed to this window and queues it to iced.
+    ///
+    /// winit 0.22 doesn't report the trackpad gestures, the view recognizes the magnification of
+    /// a pinch itself, see [`on_magnify`].
+    ///
+    /// [`on_magnify`]: #method.on_magnify
+    pub fn process_event(&mut self, event: &WindowEvent<'_>) {
+        #[cfg(feature = "json")]
+        {
+            if let Some(Err(error)) = self
+                .event_recorder
+                .as_mut()
+                .map(|event_recorder| event_recorder.record(event))
+            {
+                tracing::error!(
+                    "Failed to record an event, stopping the recording: {}",
+                    error
+                );
+                self.event_recorder = None;
+            }
+        }
+
+        let consumed = match &mut self.event_filter {
+            Some(event_filter) => event_filter(event) == Filter::Consume,
+            None => false,
+        };
+
+        match event {
+            WindowEvent::Focused(false) => {
+                // the user has to be able to switch to another window
+                if self.cursor_grab {
+                    self.grab_cursor(false);
+                }
+            }
+            WindowEvent::Focused(true) => {
+                if self.cursor_grab {
+                    self.grab_cursor(true);
+                }
+
+                // hosts don't hand the keyboard focus to our view, so we take it
+                self.focus();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = position.to_logical(self.event_scale_factor());
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                ..
+            } => {
+                // nor when the user clicks us inside a focused parent
+                self.focus();
+
+                if !consumed {
+                    self.snap_consecutive_click();
+                }
+            }
+            // a live resize reports a flood of sizes, only the last one before the next update or
+            // frame is applied
+            WindowEvent::Resized(new_size) => {
+                let logical_size = new_size.to_logical::<f64>(self.window.scale_factor());
+
+                self.resizes
+                    .report(logical_size.to_physical(self.scale_factor));
+            }
+            // e.g. the window has moved between Retina and non-Retina displays
+            WindowEvent::ScaleFactorChanged { .. } => {
+                self.update_scale_factor();
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } if !consumed && Some(*key) == self.debug_hotkey => {
+                self.set_debug_overlay(!self.debug_overlay);
+
+                return;
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::Tab),
+                        ..
+                    },
+                ..
+            } if !consumed => {
+                if let Some(tab_message) = self.tab_message {
+                    self.queue_message(tab_message(self.input.modifiers().shift()));
+                }
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } if !consumed => {
+                if let Some(message) = self.key_message.and_then(|key_message| key_message(*key)) {
+                    self.queue_message(message);
+                }
+            }
+            // the drops on a top-level window on macOS, and on Windows, where the child window
+            // is a drop target of its own
+            WindowEvent::HoveredFile(path) if !consumed => {
+                self.file_drop(FileDrop::Hovered(path.clone()));
+            }
+            WindowEvent::DroppedFile(path) if !consumed => {
+                self.file_drop(FileDrop::Dropped(path.clone()));
+            }
+            WindowEvent::HoveredFileCancelled if !consumed => {
+                self.file_drop(FileDrop::Cancelled);
+            }
+            _ => {}
+        }
+
+        // Map window event to iced event. winit has scaled the positions by the scale factor of
+        // the window it created the view in, which the parent may not share. The consumed ones
+        // are only tracked, iced doesn't get them.
+        if let Some(event) = self.input.convert(
+            event,
+            self.event_scale_factor(),
+            self.units_per_point(),
+            consumed,
+        ) {
+            self.queue_event(event);
+        }
+    }
+
+    /// Moves the cursor of the program back to the previous press if this one continues a
+    /// double-click, because iced counts the consecutive clicks only at exactly the same position,
+    /// e.g. to select a word in a text input.
+    ///
+    /// The clicks are consecutive within the double-click interval of the system, the
+    /// `doubleClickInterval` of `NSEvent` on macOS and `GetDoubleClickTime` on Windows, then iced
+    /// still limits them to its own 300 ms.
+    fn snap_consecutive_click(&mut self) {
+        let now = Instant::now();
+
+        if let Some((time, position)) = self.last_press {
+            let dx = self.cursor_position.x - position.x;
+            let dy = self.cursor_position.y - position.y;
+
+            if now - time <= double_click_interval()
+                && dx.abs() <= DOUBLE_CLICK_DISTANCE
+                && dy.abs() <= DOUBLE_CLICK_DISTANCE
+            {
+                self.cursor_position = position;
+                self.queue_event(Event::Mouse(mouse::Event::CursorMoved {
+                    x: position.x as f32,
+                    y: position.y as f32,
+                }));
+            }
+        }
+
+        self.last_press = Some((now, self.cursor_position));
+    }
+
+    /// Sets the callback deciding whether the window closes when the user asks to close it,
+    /// e.g. to confirm discarding changes, or to notify a host that the editor was closed.
+    ///
+    /// Without it only a top-level window closes, an embedded one is torn down by its host.
+    /// See [`close_requested`].
+    ///
+    /// [`close_requested`]: #method.close_requested
+    pub fn on_close_requested(&mut self, on_close_requested: impl FnMut() -> bool + 'static) {
+        self.on_close_requested = Some(Box::new(on_close_requested));
+    }
+
+    /// Asks the callback set with [`on_close_requested`] whether to close, and returns it.
+    ///
+    /// Call it on `WindowEvent::CloseRequested` and exit the event loop or drop the window only
+    /// if it returns `true`. [`tick`] calls it too, but leaves the teardown to the host, so a
+    /// plugin drops the window when the host destroys the editor.
+    ///
+    /// [`on_close_requested`]: #method.on_close_requested
+    /// [`tick`]: #method.tick
+    pub fn close_requested(&mut self) -> bool {
+        match &mut self.on_close_requested {
+            Some(on_close_requested) => on_close_requested(),
+            None => !self.embedded,
+        }
+    }
+
+    /// Turns the files dragged onto the window into messages to the program with
+    /// `on_file_drop`, e.g. to load a sample. The drags it returns `None` for are ignored.
+    ///
+    /// It works while the window is embedded too: on macOS the view registers for the dragged
+    /// files itself, because winit receives them only in the window it has created.
+    pub fn on_file_drop(&mut self, on_file_drop: fn(FileDrop) -> Option<P::Message>) {
+        self.on_file_drop = Some(on_file_drop);
+    }
+
+    fn file_drop(&mut self, file_drop: FileDrop) {
+        if let Some(message) = self
+            .on_file_drop
+            .and_then(|on_file_drop| on_file_drop(file_drop))
+        {
+            self.queue_message(message);
+        }
+    }
+
+    /// Turns the pinches on a trackpad into messages to the program with `on_magnify`, e.g. to
+    /// zoom a waveform. It's called with the change of the magnification since the last step of
+    /// the pinch, e.g. 0.1 for 10 % larger, and the logical position of the pinch. The steps it
+    /// returns `None` for are ignored.
+    ///
+    /// winit 0.22 doesn't report them, so the view recognizes them itself on macOS, also while
+    /// it's embedded. There are none on the other platforms.
+    pub fn on_magnify(&mut self, on_magnify: fn(f32, Point) -> Option<P::Message>) {
+        self.on_magnify = Some(on_magnify);
+    }
+
+    fn magnify(&mut self, magnification: Magnification) {
+        // the position is in points
+        let units_per_point = self.units_per_point();
+        let position = Point::new(
+            (magnification.x * units_per_point) as f32,
+            (magnification.y * units_per_point) as f32,
+        );
+
+        if let Some(message) = self
+            .on_magnify
+            .and_then(|on_magnify| on_magnify(magnification.delta as f32, position))
+        {
+            self.queue_message(message);
+        }
+    }
+
+    /// Sets the filter that sees every window event before iced, e.g. to handle a global
+    /// shortcut of the host or to keep some keys from the GUI.
+    ///
+    /// The events it consumes still resize and focus the window, they just aren't queued to
+    /// iced.
+    pub fn set_event_filter(
+        &mut self,
+        event_filter: impl FnMut(&WindowEvent<'_>) -> Filter + 'static,
+    ) {
+        self.event_filter = Some(Box::new(event_filter));
+    }
+
+    /// Records the window events it processes with the `event_recorder`, before they're
+    /// filtered, or stops recording with `None`.
+    #[cfg(feature = "json")]
+    pub fn set_event_recorder(&mut self, event_recorder: Option<EventRecorder>) {
+        self.event_recorder = event_recorder;
+    }
+
+    /// Handles a winit device event.
+    ///
+    /// Only the mouse motion is used, to move the cursor of the program while the cursor is
+    /// grabbed on macOS, where the system cursor stays in place then.
+    pub fn process_device_event(&mut self, event: &DeviceEvent) {
+        match event {
+            DeviceEvent::MouseMotion { delta: (x, y) }
+                if self.cursor_grab && cfg!(target_os = "macos") =>
+            {
+                // the deltas are in points
+                let units_per_point = self.units_per_point();
+                self.cursor_position.x += x * units_per_point;
+                self.cursor_position.y += y * units_per_point;
+
+                self.queue_event(Event::Mouse(mouse::Event::CursorMoved {
+                    x: self.cursor_position.x as f32,
+                    y: self.cursor_position.y as f32,
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    /// Queues the iced `event` to the program and to its subscriptions.
+    ///
+    /// The cursor moves are coalesced until the next update, any other event queues the latest
+    /// move before it.
+    fn queue_event(&mut self, event: Event) {
+        if let Some(event) = self.input.coalesce(event) {
+            self.flush_cursor_moved();
+            self.dispatch_event(event);
+        }
+    }
+
+    fn flush_resize(&mut self) {
+        if let Some(physical_size) = self.resizes.flush() {
+            self.resize_viewport(physical_size);
+        }
+    }
+
+    fn flush_cursor_moved(&mut self) {
+        if let Some(event) = self.input.take_cursor_moved() {
+            self.dispatch_event(event);
+        }
+    }
+
+    fn dispatch_event(&mut self, event: Event) {
+        if let Some(runtime) = &mut self.runtime {
+            runtime.broadcast(event.clone());
+        }
+
+        self.state.queue_event(event);
+        self.dirty = true;
+    }
+
+    fn overlay(&self) -> Vec<String> {
+        if self.debug_overlay {
+            self.debug.overlay()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn can_render(&self) -> bool {
+        !self.suspended && !self.detached && self.visible && !self.occluded
+    }
+
+    fn resize_viewport(&mut self, physical_size: PhysicalSize<u32>) {
+        // it supersedes the sizes winit has reported before
+        self.resizes.take_reported();
+
+        // e.g. the window is minimized, the next non-zero size resumes us
+        self.suspended = is_zero(physical_size);
+
+        if self.suspended {
+            return;
+        }
+
+        self.viewport = Viewport::with_physical_size(
+            Size::new(physical_size.width, physical_size.height),
+            self.ui_scale_factor(),
+        );
+
+        // the program lays out only when it receives events, and winit doesn't report the
+        // sizes we set ourselves
+        let logical_size = self.viewport.logical_size();
+        self.queue_event(Event::Window(window::Event::Resized {
+            width: logical_size.width as u32,
+            height: logical_size.height as u32,
+        }));
+        self.publish_accessibility();
+
+        // the swap chain is recreated on the next render
+        self.resizes.invalidate();
+        self.swap_chain_retries = 0;
+        self.dirty = true;
+    }
+
+    /// Follows the scale factor of the monitor the window is on, e.g. when the parent has moved
+    /// to a display with another scale, which winit may not report with `ScaleFactorChanged`.
+    fn update_scale_factor(&mut self) {
+        let scale_factor = render_scale_factor(&self.window);
+
+        if scale_factor == self.scale_factor {
+            return;
+        }
+
+        self.scale_factor = scale_factor;
+        set_contents_scale(&self.window, scale_factor);
+        self.resize_viewport(physical_size(&self.window, scale_factor));
+    }
+
+    fn recreate_swap_chain(&mut self) {
+        let size = physical_size(&self.window, self.scale_factor);
+        let span = tracing::debug_span!(
+            "recreate_swap_chain",
+            width = size.width,
+            height = size.height
+        );
+        let _enter = span.enter();
+        // in the next size, or not until resized again
+        self.resizes.recreated();
+
+        if is_zero(size) {
+            self.suspended = true;
+            return;
+        }
+
+        self.swap_chain = Some(create_swap_chain(
+            &self.device,
+            &self.surface,
+            self.format,
+            self.present_mode,
+            size,
+        ));
+        self.swap_chain_size = size;
+        metal_layer::configure(&self.window, &self.metal_layer);
+
+        // it has to match the size of the frame
+        if self.depth.is_some() {
+            self.depth = Some(create_depth(&self.device, size));
+        }
+    }
+
+    /// Updates iced with the queued events and requests a redraw according to the
+    /// [`RenderSchedule`].
+    ///
+    /// [`RenderSchedule`]: enum.RenderSchedule.html
+    ///
+    /// Call it on `Event::MainEventsCleared`.
+    pub fn update(&mut self) {
+        let occluded = is_occluded(&self.window);
+
+        if self.occluded && !occluded {
+            // repaint the stale content
+            self.dirty = true;
+        }
+
+        self.occluded = occluded;
+
+        self.update_theme();
+        self.flush_resize();
+        self.update_scale_factor();
+
+        let file_drops = match &self.drop_target {
+            Some(drop_target) => drop_target.take_drops(),
+            None => Vec::new(),
+        };
+
+        for file_drop in file_drops {
+            self.file_drop(file_drop);
+        }
+
+        let magnifications = match &self.magnify_recognizer {
+            Some(magnify_recognizer) => magnify_recognizer.take_magnifications(),
+            None => Vec::new(),
+        };
+
+        for magnification in magnifications {
+            self.magnify(magnification);
+        }
+
+        if let Some((interval, tick)) = self.tick {
+            let now = Instant::now();
+
+            if now - self.last_tick >= interval {
+                self.last_tick = now;
+                self.queue_message(tick(now));
+            }
+        }
+
+        self.flush_cursor_moved();
+
+        let span = tracing::trace_span!(
+            "update",
+            frame = self.frame_timer.frame(),
+            coalesced_cursor_moves = self.input.take_coalesced_cursor_moves(),
+            coalesced_resizes = self.resizes.take_coalesced(),
+            update_time = tracing::field::Empty
+        );
+        let _enter = span.enter();
+
+        let update_start = Instant::now();
+        let command = self.state.update(
+            Some(&clipboard::Bridge(&*self.clipboard)),
+            self.viewport.logical_size(),
+            &mut self.renderer,
+            &mut self.debug,
+        );
+
+        self.apply_cursor_requests();
+
+        if let Some(command) = command {
+            let update_time = update_start.elapsed();
+
+            span.record("update_time", &tracing::field::debug(update_time));
+            self.frame_timer.record_update(update_time);
+            self.dirty = true;
+
+            match (&mut self.runtime, &mut self.on_command) {
+                (Some(runtime), _) => {
+                    runtime.spawn(command);
+
+                    if let Some(subscription) = self.subscription {
+                        runtime.track(subscription(&self.state.program().program));
+                    }
+                }
+                (None, Some(on_command)) => on_command(command),
+                // the program can't do anything async then
+                (None, None) => {}
+            }
+
+            if let Some(background_color) = self.background_color {
+                self.clear_color = linear_color(background_color(self.program()));
+            }
+
+            self.publish_accessibility();
+            self.update_ime_position();
+        }
+
+        // the frame is requested on a later update, a dirty, ticked or requested one isn't
+        // dropped, so they're taken only once it can be
+        if self.is_capped() {
+            return;
+        }
+
+        let redraw = match self.render_schedule {
+            RenderSchedule::Continuous => true,
+            RenderSchedule::OnDemand => self.dirty,
+            RenderSchedule::Throttled { fps } => {
+                self.last_frame.elapsed() >= schedule::frame_interval(fps)
+            }
+            RenderSchedule::DisplayLink => match &self.display_link {
+                Some(display_link) => display_link.take_tick(),
+                None => {
+                    self.last_frame.elapsed() >= schedule::frame_interval(schedule::FALLBACK_FPS)
+                }
+            },
+        };
+
+        // e.g. by the host through a RedrawHandle
+        let requested = self.redraw_requested.swap(false, Ordering::AcqRel);
+
+        if (redraw || requested) && self.can_render() {
+            self.window.request_redraw();
+        }
+    }
+
+    /// Draws a frame.
+    ///
+    /// Call it on `Event::RedrawRequested`.
+    pub fn render(&mut self) {
+        // winit may redraw during a live resize without an update in between
+        self.flush_resize();
+
+        if !self.can_render() {
+            return;
+        }
+
+        let span = tracing::trace_span!(
+            "render",
+            frame = self.frame_timer.frame(),
+            draw_time = tracing::field::Empty,
+            submit_time = tracing::field::Empty
+        );
+        let _enter = span.enter();
+
+        if self.resizes.is_stale() || self.swap_chain.is_none() {
+            self.recreate_swap_chain();
+        }
+
+        // wgpu doesn't report suboptimal frames, but a frame of the previous size would be
+        // stretched, e.g. when the host animates the size of the parent and we race its resizes
+        let size = physical_size(&self.window, self.scale_factor);
+
+        if size != self.swap_chain_size && self.suboptimal_retries < MAX_SUBOPTIMAL_RETRIES {
+            tracing::warn!(
+                "The swap chain has the size {:?} and the window {:?}, recreating it",
+                self.swap_chain_size,
+                size
+            );
+            self.suboptimal_retries += 1;
+            self.resize_viewport(size);
+
+            if !self.can_render() {
+                return;
+            }
+
+            self.recreate_swap_chain();
+        } else if size == self.swap_chain_size {
+            self.suboptimal_retries = 0;
+        }
+
+        // the timeout is the only error wgpu reports here, it happens when the surface becomes
+        // outdated, e.g. after the display configuration has changed
+        let swap_chain = match &mut self.swap_chain {
+            Some(swap_chain) => swap_chain,
+            None => return,
+        };
+
+        let frame = match swap_chain.get_next_texture() {
+            Ok(frame) => {
+                self.swap_chain_retries = 0;
+                frame
+            }
+            Err(_) if self.swap_chain_retries < MAX_SWAP_CHAIN_RETRIES => {
+                tracing::warn!("Timed out acquiring the next frame, recreating the swap chain");
+                self.swap_chain_retries += 1;
+                self.recreate_swap_chain();
+                self.window.request_redraw();
+                return;
+            }
+            Err(_) => {
+                // once per failure, the frames are skipped until resized if it doesn't help
+                if self.swap_chain_retries == MAX_SWAP_CHAIN_RETRIES {
+                    self.swap_chain_retries += 1;
+
+                    tracing::warn!("The swap chain keeps timing out, reinitializing the GPU");
+
+                    match self.reinitialize_gpu() {
+                        Ok(()) => self.window.request_redraw(),
+                        Err(error) => tracing::error!(
+                            "Failed to reinitialize the GPU, skipping frames until resized: {}",
+                            error
+                        ),
+                    }
+                }
+
+                return;
+            }
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        let overlay = self.overlay();
+        let size = Size::new(self.swap_chain_size.width, self.swap_chain_size.height);
+
+        let redraw = match &mut self.damage {
+            Some(damage) => {
+                // they draw outside of the primitive tree
+                if !overlay.is_empty() || self.pre_render.is_some() {
+                    damage.invalidate();
+                }
+
+                damage.prepare(
+                    &self.device,
+                    size,
+                    &self.state.primitive().0,
+                    self.viewport.scale_factor(),
+                    self.clear_color,
+                )
+            }
+            None => Redraw::Whole,
+        };
+        let target = self
+            .damage
+            .as_ref()
+            .and_then(DamageTracker::target)
+            .unwrap_or(&frame.view);
+
+        let draw_start = Instant::now();
+        let mouse_interaction = match redraw {
+            Redraw::Whole => {
+                let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                        attachment: target,
+                        resolve_target: None,
+                        load_op: wgpu::LoadOp::Clear,
+                        store_op: wgpu::StoreOp::Store,
+                        clear_color: self.clear_color,
+                    }],
+                    depth_stencil_attachment: self.depth.as_ref().map(|(_, view)| {
+                        wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                            attachment: view,
+                            depth_load_op: wgpu::LoadOp::Clear,
+                            depth_store_op: wgpu::StoreOp::Store,
+                            clear_depth: 1.0,
+                            stencil_load_op: wgpu::LoadOp::Clear,
+                            stencil_store_op: wgpu::StoreOp::Store,
+                            clear_stencil: 0,
+                        }
+                    }),
+                });
+
+                if let Some(background) = &self.background {
+                    background.draw(&self.device, &mut encoder, target, size, None);
+                }
+
+                if let Some(pre_render) = &mut self.pre_render {
+                    pre_render(&mut self.device, &mut encoder, target);
+                }
+
+                // And then iced on top
+                self.renderer.backend_mut().draw(
+                    &mut self.device,
+                    &mut encoder,
+                    target,
+                    &self.viewport,
+                    self.state.primitive(),
+                    &overlay,
+                )
+            }
+            Redraw::Region(region) => {
+                if let Some(damage) = &self.damage {
+                    damage.clear(&self.device, &mut encoder, region);
+                }
+
+                if let Some(background) = &self.background {
+                    background.draw(&self.device, &mut encoder, target, size, Some(region));
+                }
+
+                // iced scissors the layer of a clip to its bounds, the rest of the target keeps
+                // the last frame
+                let scale_factor = self.viewport.scale_factor() as f32;
+                let (primitive, mouse_interaction) = self.state.primitive();
+                let clipped = Primitive::Clip {
+                    bounds: Rectangle {
+                        x: region.x as f32 / scale_factor,
+                        y: region.y as f32 / scale_factor,
+                        width: region.width as f32 / scale_factor,
+                        height: region.height as f32 / scale_factor,
+                    },
+                    offset: Vector::new(0, 0),
+                    content: Box::new(primitive.clone()),
+                };
+
+                self.renderer.backend_mut().draw(
+                    &mut self.device,
+                    &mut encoder,
+                    target,
+                    &self.viewport,
+                    &(clipped, *mouse_interaction),
+                    &overlay,
+                )
+            }
+            Redraw::Nothing => self.state.primitive().1,
+        };
+        let draw_time = draw_start.elapsed();
+
+        if let Some(damage) = &self.damage {
+            damage.present(&self.device, &mut encoder, &frame.view, size);
+        }
+
+        // Then we submit the work
+        let submit_start = Instant::now();
+        self.queue.submit(&[encoder.finish()]);
+        let submit_time = submit_start.elapsed();
+
+        span.record("draw_time", &tracing::field::debug(draw_time));
+        span.record("submit_time", &tracing::field::debug(submit_time));
+        self.frame_timer.record_frame(draw_time, submit_time);
+        self.last_frame = Instant::now();
+        self.dirty = false;
+
+        // And update the mouse cursor
+        self.set_cursor_icon(iced_winit::conversion::mouse_interaction(mouse_interaction));
+    }
+
+    /// Runs one iteration of the `event_loop`: handles the pending events, updates the program and
+    /// renders a frame if it's needed. Returns whether a frame was rendered.
+    ///
+    /// It doesn't block, so a plugin can call it from the idle callback of the host, at its own
+    /// pace, instead of running the loop. The user events are queued to the program as messages,
+    /// like the ones of a [`MessageSender`].
+    ///
+    /// [`MessageSender`]: struct.MessageSender.html
+    pub fn tick(&mut self, event_loop: &mut EventLoop<P::Message>) -> bool {
+        let window_id = self.window.id();
+        let last_frame = self.last_frame;
+
+        event_loop.run_return(|event, _, control_flow| match event {
+            // the event loop belongs to the host, it isn't exited
+            WinitEvent::WindowEvent {
+                window_id: id,
+                event: WindowEvent::CloseRequested,
+            } if id == window_id => {
+                let _ = self.close_requested();
+            }
+            WinitEvent::WindowEvent {
+                window_id: id,
+                event,
+            } if id == window_id => self.process_event(&event),
+            WinitEvent::DeviceEvent { event, .. } => self.process_device_event(&event),
+            WinitEvent::UserEvent(message) => self.queue_message(message),
+            WinitEvent::MainEventsCleared => self.update(),
+            WinitEvent::RedrawRequested(id) if id == window_id => self.render(),
+            // the iteration is over, return to the host
+            WinitEvent::RedrawEventsCleared => *control_flow = ControlFlow::Exit,
+            // don't wait for events until the iteration is over
+            _ => *control_flow = ControlFlow::Poll,
+        });
+
+        self.last_frame != last_frame
+    }
+
+    /// Requests a new adapter and device and recreates everything rendering with them, keeping
+    /// the state of the program.
+    ///
+    /// Call it when the GPU is lost, e.g. after a driver reset or when an eGPU is unplugged. The
+    /// wgpu version we use can't report it, a swap chain that keeps timing out is the only
+    /// symptom we see, so [`render`] calls it then too. The callback set with
+    /// [`set_on_gpu_reset`] is notified on success.
+    ///
+    /// It requests the device itself even if the window was built [`with_device`].
+    ///
+    /// [`with_device`]: struct.ChildWindowBuilder.html#method.with_device
+    /// [`render`]: #method.render
+    /// [`set_on_gpu_reset`]: #method.set_on_gpu_reset
+    pub fn reinitialize_gpu(&mut self) -> Result<(), InitError> {
+        let surface = create_surface(&self.window, self.transparent);
+        set_contents_scale(&self.window, self.scale_factor);
+        let (adapter, mut device, queue) = gpu::request(
+            self.power_preference,
+            self.backends,
+            self.anisotropic_filtering,
+            &self.limits,
+            Some(&surface),
+        )?;
+
+        let gpu_info = GpuInfo::from(adapter.get_info());
+        let format = format::select(gpu_info.backend, self.hdr, self.force_srgb);
+        set_extended_range(&self.window, !format::is_srgb(format));
+        let size = physical_size(&self.window, self.scale_factor);
+        let swap_chain_size = PhysicalSize::new(size.width.max(1), size.height.max(1));
+        let swap_chain = create_swap_chain(
+            &device,
+            &surface,
+            format,
+            self.present_mode,
+            swap_chain_size,
+        );
+        metal_layer::configure(&self.window, &self.metal_layer);
+        let depth = self
+            .depth
+            .as_ref()
+            .map(|_| create_depth(&device, swap_chain_size));
+        let settings = renderer_settings(format, self.sample_count, self.default_font);
+        let renderer = Renderer::new(Backend::new(&mut device, settings));
+
+        // in the order of the fields, so the old resources go before the old device
+        self.renderer = renderer;
+        // the texture of the host belongs to the old device
+        self.background = None;
+        self.damage = self
+            .damage
+            .as_ref()
+            .map(|_| DamageTracker::new(&device, format));
+        self.depth = depth;
+        self.swap_chain = Some(swap_chain);
+        self.swap_chain_size = swap_chain_size;
+        self.surface = surface;
+        self.queue = queue;
+        self.device = device;
+        self.gpu_info = gpu_info;
+        self.format = format;
+        self.dirty = true;
+
+        if let Some(on_gpu_reset) = &mut self.on_gpu_reset {
+            on_gpu_reset(&self.gpu_info);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the callback invoked after the GPU has been reinitialized with the new adapter.
+    ///
+    /// The resources of the old device are invalid then, e.g. the ones the [`set_pre_render`]
+    /// callback draws with have to be recreated, and the texture of
+    /// [`set_background_texture`] set again.
+    ///
+    /// [`set_pre_render`]: #method.set_pre_render
+    /// [`set_background_texture`]: #method.set_background_texture
+    pub fn set_on_gpu_reset(&mut self, on_gpu_reset: impl FnMut(&GpuInfo) + 'static) {
+        self.on_gpu_reset = Some(Box::new(on_gpu_reset));
+    }
+
+    /// Renders the current state of the program into a PNG file at `path`, independently of the
+    /// swap chain.
+    pub fn capture_png(&mut self, path: &Path) -> Result<(), CaptureError> {
+        if format::is_hdr(self.format) {
+            return Err(CaptureError::UnsupportedFormat(self.format));
+        }
+
+        let physical_size = self.viewport.physical_size();
+        let size = Size::new(physical_size.width.max(1), physical_size.height.max(1));
+
+        let texture = capture::create_target(&self.device, size, self.format);
+        let view = texture.create_default_view();
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: &view,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Clear,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: self.clear_color,
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        if let Some(background) = &self.background {
+            background.draw(&self.device, &mut encoder, &view, size, None);
+        }
+
+        if let Some(pre_render) = &mut self.pre_render {
+            pre_render(&mut self.device, &mut encoder, &view);
+        }
+
+        let overlay = self.overlay();
+        let _ = self.renderer.backend_mut().draw(
+            &mut self.device,
+            &mut encoder,
+            &view,
+            &self.viewport,
+            self.state.primitive(),
+            &overlay,
+        );
+
+        let pixels = capture::read_pixels(
+            &self.device,
+            &self.queue,
+            encoder,
+            &texture,
+            size,
+            self.format,
+        )?;
+
+        capture::write_png(path, size, &pixels)
+    }
+}
+
+/// The handle of the view the program is rendered in, e.g. to create another surface over it or
+/// to pass it to another library.
+///
+/// On macOS `ns_window` is the window the view is in, i.e. the window of the host once it's
+/// embedded rather than the hidden one winit has created the view in, and null while it's
+/// suspended. On Windows it's the child `HWND`.
+unsafe impl<P: 'static + Program<Renderer = Renderer>> HasRawWindowHandle for ChildWindow<P> {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        child_window_handle(&self.window)
+    }
+}
+
+impl<P: 'static + Program<Renderer = Renderer>> Drop for ChildWindow<P> {
+    fn drop(&mut self) {
+        // they message the view, which the parent releases when it's detached below
+        self.drop_target = None;
+        self.magnify_recognizer = None;
+
+        // the parent retains our view, so it would outlive the window otherwise. On Windows
+        // destroying the window removes it from the parent.
+        #[cfg(target_os = "macos")]
+        {
+            // a top-level window owns its view, it's its content view
+            if self.embedded {
+                unsafe { cocoa_util::detach_view(self.window.ns_view() as id) };
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn child_window_handle(window: &Window) -> RawWindowHandle {
+    match window.raw_window_handle() {
+        RawWindowHandle::MacOS(handle) => {
+            let ns_window = unsafe { cocoa_util::view_window(handle.ns_view as id) };
+
+            RawWindowHandle::MacOS(raw_window_handle::macos::MacOSHandle {
+                ns_window: ns_window.map_or(std::ptr::null_mut(), |window| window as *mut _),
+                ..handle
+            })
+        }
+        handle => handle,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn child_window_handle(window: &Window) -> RawWindowHandle {
+    window.raw_window_handle()
+}
+
+fn start_display_link(render_schedule: RenderSchedule) -> Option<DisplayLink> {
+    match render_schedule {
+        RenderSchedule::DisplayLink => DisplayLink::new(),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn create_drop_target(window: &Window) -> Option<DropTarget> {
+    Some(unsafe { DropTarget::new(window.ns_view() as id) })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn create_drop_target(_window: &Window) -> Option<DropTarget> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn create_magnify_recognizer(window: &Window) -> Option<MagnifyRecognizer> {
+    Some(unsafe { MagnifyRecognizer::new(window.ns_view() as id) })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn create_magnify_recognizer(_window: &Window) -> Option<MagnifyRecognizer> {
+    None
+}
+
+fn create_surface(window: &Window, transparent: bool) -> wgpu::Surface {
+    let surface = wgpu::Surface::create(window);
+
+    // wgpu has just backed the view with a CAMetalLayer
+    #[cfg(target_os = "macos")]
+    {
+        if transparent {
+            unsafe { cocoa_util::set_layer_opaque(window.ns_view() as id, false) };
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    let _ = transparent;
+
+    surface
+}
+
+fn create_swap_chain(
+    device: &wgpu::Device,
+    surface: &wgpu::Surface,
+    format: wgpu::TextureFormat,
+    present_mode: wgpu::PresentMode,
+    size: PhysicalSize<u32>,
+) -> wgpu::SwapChain {
+    device.create_swap_chain(
+        surface,
+        &wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+            format,
+            width: size.width,
+            height: size.height,
+            present_mode,
+        },
+    )
+}
+
+fn create_depth(
+    device: &wgpu::Device,
+    size: PhysicalSize<u32>,
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth: 1,
+        },
+        array_layer_count: 1,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+    });
+    let view = texture.create_default_view();
+
+    (texture, view)
+}
+
+/// The scale factor to render with, the one of the monitor the window is mostly on.
+///
+/// It's the backing scale factor of the window hosting the view on macOS, which isn't the one
+/// winit created it in once it's embedded.
+#[cfg(target_os = "macos")]
+fn render_scale_factor(window: &Window) -> f64 {
+    unsafe {
+        cocoa_util::view_window(window.ns_view() as id)
+            .map(|parent| cocoa_util::backing_scale_factor(parent))
+            .unwrap_or_else(|| window.scale_factor())
+    }
+}
+
+/// Elsewhere it's the scale factor of the current monitor, because a child window doesn't get
+/// notified when its top-level window moves to another one, so winit misses the change.
+#[cfg(not(target_os = "macos"))]
+fn render_scale_factor(window: &Window) -> f64 {
+    window.current_monitor().scale_factor()
+}
+
+/// Makes the layer of the view map the frames to points with the `scale_factor` they're
+/// rendered with.
+#[cfg(target_os = "macos")]
+fn set_contents_scale(window: &Window, scale_factor: f64) {
+    unsafe { cocoa_util::set_layer_contents_scale(window.ns_view() as id, scale_factor) };
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_contents_scale(_window: &Window, _scale_factor: f64) {}
+
+/// Tags the layer of the view with the linear color space of the formats wgpu doesn't encode to
+/// sRGB, the HDR ones included.
+#[cfg(target_os = "macos")]
+fn set_extended_range(window: &Window, extended_range: bool) {
+    unsafe { cocoa_util::set_layer_extended_range(window.ns_view() as id, extended_range) };
+}
+
+/// The float swap chain of DXGI is scRGB already, the other linear ones can't be tagged.
+#[cfg(not(target_os = "macos"))]
+fn set_extended_range(_window: &Window, _extended_range: bool) {}
+
+/// The size of the window in the pixels it's rendered in with the `scale_factor`.
+///
+/// winit measures the physical size with the scale factor of its own window.
+fn physical_size(window: &Window, scale_factor: f64) -> PhysicalSize<u32> {
+    rescale(window.inner_size(), window.scale_factor(), scale_factor)
+}
+
+/// Converts the physical `size` of the `old_scale_factor` to the pixels of the
+/// `new_scale_factor`, e.g. when the window has moved to a monitor with another scale.
+fn rescale(
+    size: PhysicalSize<u32>,
+    old_scale_factor: f64,
+    new_scale_factor: f64,
+) -> PhysicalSize<u32> {
+    size.to_logical::<f64>(old_scale_factor)
+        .to_physical(new_scale_factor)
+}
+
+/// Whether the window hosting the view of the child `window` isn't visible on the screen, e.g.
+/// because it's covered by other windows or is on another space.
+#[cfg(target_os = "macos")]
+fn is_occluded(window: &Window) -> bool {
+    unsafe {
+        match cocoa_util::view_window(window.ns_view() as id) {
+            Some(parent) => {
+                cocoa_util::occlusion_state(parent) & NS_WINDOW_OCCLUSION_STATE_VISIBLE == 0
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_occluded(_window: &Window) -> bool {
+    false
+}
+
+/// Makes the view of the child `window` the first responder of the window hosting it.
+///
+/// The winit view accepts the first responder status, the host window just has to offer it.
+#[cfg(target_os = "macos")]
+fn make_first_responder(window: &Window) {
+    unsafe {
+        let view = window.ns_view() as id;
+
+        if let Some(parent) = cocoa_util::view_window(view) {
+            let _ = cocoa_util::make_first_responder(parent, view);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn make_first_responder(window: &Window) {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::SetFocus;
+
+    if let RawWindowHandle::Windows(handle) = window.raw_window_handle() {
+        unsafe { SetFocus(handle.hwnd as HWND) };
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn make_first_responder(_window: &Window) {}
+
+/// Makes the dialog loop of the host leave Tab to the window.
+#[cfg(target_os = "windows")]
+fn claim_tab(window: &Window) {
+    use winapi::shared::basetsd::{DWORD_PTR, UINT_PTR};
+    use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+    use winapi::shared::windef::HWND;
+    use winapi::um::commctrl::{DefSubclassProc, SetWindowSubclass};
+    use winapi::um::winuser::{DLGC_WANTTAB, WM_GETDLGCODE};
+
+    // any id unique among the subclasses of the window, winit uses its own
+    const SUBCLASS_ID: UINT_PTR = 0x7461_6221;
+
+    unsafe extern "system" fn subclass_proc(
+        hwnd: HWND,
+        msg: UINT,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _id: UINT_PTR,
+        _data: DWORD_PTR,
+    ) -> LRESULT {
+        let result = DefSubclassProc(hwnd, msg, wparam, lparam);
+
+        if msg == WM_GETDLGCODE {
+            result | DLGC_WANTTAB as LRESULT
+        } else {
+            result
+        }
+    }
+
+    if let RawWindowHandle::Windows(handle) = window.raw_window_handle() {
+        let claimed =
+            unsafe { SetWindowSubclass(handle.hwnd as HWND, Some(subclass_proc), SUBCLASS_ID, 0) };
+
+        if claimed == 0 {
+            tracing::warn!("Failed to claim the Tab key");
+        }
+    }
+}
+
+/// The first responder gets Tab before the key view loop of its window.
+#[cfg(not(target_os = "windows"))]
+fn claim_tab(_window: &Window) {}
+
+#[cfg(target_os = "macos")]
+fn set_ime_position(window: &Window, position: Point) {
+    let position = unsafe {
+        cocoa_util::position_in_content(
+            window.ns_view() as id,
+            NSPoint::new(position.x as f64, position.y as f64),
+        )
+    };
+
+    window.set_ime_position(LogicalPosition::new(position.x, position.y));
+}
+
+/// The position is in the client area of the window, wherever it's embedded.
+#[cfg(not(target_os = "macos"))]
+fn set_ime_position(window: &Window, position: Point) {
+    window.set_ime_position(LogicalPosition::new(position.x as f64, position.y as f64));
+}
+
+#[cfg(target_os = "macos")]
+fn double_click_interval() -> Duration {
+    Duration::from_secs_f64(unsafe { cocoa_util::double_click_interval() })
+}
+
+#[cfg(target_os = "windows")]
+fn double_click_interval() -> Duration {
+    use winapi::um::winuser::GetDoubleClickTime;
+
+    Duration::from_millis(unsafe { GetDoubleClickTime() } as u64)
+}
+
+/// The default of macOS and Windows.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn double_click_interval() -> Duration {
+    Duration::from_millis(500)
+}
+
+/// winit would order out the window it has created the view in, which doesn't host it anymore.
+#[cfg(target_os = "macos")]
+fn set_window_visible(window: &Window, embedded: bool, visible: bool) {
+    if embedded {
+        unsafe { cocoa_util::set_view_hidden(window.ns_view() as id, !visible) };
+    } else {
+        window.set_visible(visible);
+    }
+}
+
+/// A child window is shown and hidden like a top-level one.
+#[cfg(not(target_os = "macos"))]
+fn set_window_visible(window: &Window, _embedded: bool, visible: bool) {
+    window.set_visible(visible);
+}
+
+#[cfg(target_os = "macos")]
+fn set_parent_always_on_top(window: &Window, always_on_top: bool) {
+    unsafe {
+        if let Some(parent) = cocoa_util::view_window(window.ns_view() as id) {
+            cocoa_util::set_floating(parent, always_on_top);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_parent_always_on_top(window: &Window, always_on_top: bool) {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        GetAncestor, SetWindowPos, GA_ROOT, HWND_NOTOPMOST, HWND_TOPMOST, SWP_NOACTIVATE,
+        SWP_NOMOVE, SWP_NOSIZE,
+    };
+
+    let insert_after = if always_on_top {
+        HWND_TOPMOST
+    } else {
+        HWND_NOTOPMOST
+    };
+
+    if let RawWindowHandle::Windows(handle) = window.raw_window_handle() {
+        unsafe {
+            SetWindowPos(
+                GetAncestor(handle.hwnd as HWND, GA_ROOT),
+                insert_after,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            )
+        };
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn set_parent_always_on_top(_window: &Window, _always_on_top: bool) {}
+
+#[cfg(target_os = "macos")]
+fn set_parent_title(window: &Window, title: &str) {
+    unsafe {
+        if let Some(parent) = cocoa_util::view_window(window.ns_view() as id) {
+            cocoa_util::set_window_title(parent, title);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_parent_title(window: &Window, title: &str) {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{GetAncestor, SetWindowTextW, GA_ROOT};
+
+    let title: Vec<u16> = OsStr::new(title).encode_wide().chain(Some(0)).collect();
+
+    if let RawWindowHandle::Windows(handle) = window.raw_window_handle() {
+        unsafe { SetWindowTextW(GetAncestor(handle.hwnd as HWND, GA_ROOT), title.as_ptr()) };
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn set_parent_title(_window: &Window, _title: &str) {}
+
+fn renderer_settings(
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    default_font: Option<&'static [u8]>,
+) -> Settings {
+    Settings {
+        format,
+        default_font,
+        antialiasing: antialiasing(sample_count),
+        ..Settings::default()
+    }
+}
+
+fn antialiasing(sample_count: u32) -> Option<Antialiasing> {
+    match sample_count {
+        1 => None,
+        2 => Some(Antialiasing::MSAAx2),
+        4 => Some(Antialiasing::MSAAx4),
+        8 => Some(Antialiasing::MSAAx8),
+        16 => Some(Antialiasing::MSAAx16),
+        _ => {
+            tracing::warn!("Unsupported sample count {}, disabling MSAA", sample_count);
+            None
+        }
+    }
+}
+
+/// Clamps the logical `size` between the `min` and `max` sizes.
+///
+/// A zero size means the parent is collapsed, we render nothing then, so it isn't clamped.
+fn clamp_size(size: Size, min: Option<Size>, max: Option<Size>) -> Size {
+    if size.width == 0.0 || size.height == 0.0 {
+        return size;
+    }
+
+    let Size {
+        mut width,
+        mut height,
+    } = size;
+
+    if let Some(min) = min {
+        width = width.max(min.width);
+        height = height.max(min.height);
+    }
+
+    if let Some(max) = max {
+        width = width.min(max.width);
+        height = height.min(max.height);
+    }
+
+    Size::new(width, height)
+}
+
+fn is_zero(size: PhysicalSize<u32>) -> bool {
+    size.width == 0 || size.height == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_the_scale_of_another_monitor() {
+        // winit still measures the window on the Retina display it has left
+        let size = rescale(PhysicalSize::new(800, 600), 2.0, 1.0);
+        let viewport = Viewport::with_physical_size(Size::new(size.width, size.height), 1.0);
+
+        assert_eq!(size, PhysicalSize::new(400, 300));
+        assert_eq!(viewport.scale_factor(), 1.0);
+        assert_eq!(viewport.logical_size(), Size::new(400.0, 300.0));
+
+        // and back
+        let size = rescale(size, 1.0, 2.0);
+        let viewport = Viewport::with_physical_size(Size::new(size.width, size.height), 2.0);
+
+        assert_eq!(size, PhysicalSize::new(800, 600));
+        assert_eq!(viewport.scale_factor(), 2.0);
+        assert_eq!(viewport.logical_size(), Size::new(400.0, 300.0));
+    }
+}
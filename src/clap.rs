@@ -0,0 +1,192 @@
+//! An editor for CLAP plugins, implementing the `gui` extension on top of a [`ChildWindow`].
+//!
+//! The plugin forwards the calls of its `clap_plugin_gui` to a [`ClapEditor`]: `create`,
+//! `set_parent`, `set_size`, `show`, `hide` and `destroy`, and calls [`ClapEditor::tick`] from
+//! the timer or the idle callback the host gives it. No CLAP bindings are needed, the window
+//! handle of the host is read through [`ClapWindow`].
+//!
+//! [`ChildWindow`]: ../struct.ChildWindow.html
+//! [`ClapEditor`]: struct.ClapEditor.html
+//! [`ClapEditor::tick`]: struct.ClapEditor.html#method.tick
+//! [`ClapWindow`]: struct.ClapWindow.html
+
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_char;
+
+use iced_wgpu::Renderer;
+use iced_winit::{winit::event_loop::EventLoop, Program, Size};
+
+use crate::{ChildWindow, ChildWindowBuilder, EmbedError, InitError};
+
+/// The `CLAP_WINDOW_API_COCOA` the parent of a macOS host is given with.
+pub const WINDOW_API_COCOA: &str = "cocoa";
+
+/// The `CLAP_WINDOW_API_WIN32` the parent of a Windows host is given with.
+pub const WINDOW_API_WIN32: &str = "win32";
+
+/// The `clap_window_t` of the host, with the same layout, so a `*const clap_window_t` can be
+/// cast to it.
+///
+/// `specific` is the union of the handles: the `NSView` for `cocoa`, the `HWND` for `win32`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ClapWindow {
+    pub api: *const c_char,
+    pub specific: *mut c_void,
+}
+
+impl ClapWindow {
+    /// The `api` as a string, `None` if it's null or not UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// `api` must be null or a valid C string.
+    pub unsafe fn api(&self) -> Option<&str> {
+        if self.api.is_null() {
+            None
+        } else {
+            CStr::from_ptr(self.api).to_str().ok()
+        }
+    }
+}
+
+/// Whether an editor can be embedded with the window `api`, for `is_api_supported`. Floating
+/// editors are supported on every platform.
+pub fn is_api_supported(api: &str, is_floating: bool) -> bool {
+    is_floating
+        || (cfg!(target_os = "macos") && api == WINDOW_API_COCOA)
+        || (cfg!(target_os = "windows") && api == WINDOW_API_WIN32)
+}
+
+/// A [`ChildWindow`] driven through the `gui` extension of CLAP.
+///
+/// Each editor runs its own winit event loop, one iteration per [`tick`].
+///
+/// [`ChildWindow`]: ../struct.ChildWindow.html
+/// [`tick`]: #method.tick
+pub struct ClapEditor<P: 'static + Program<Renderer = Renderer>> {
+    child_window: ChildWindow<P>,
+    event_loop: EventLoop<P::Message>,
+}
+
+impl<P: 'static + Program<Renderer = Renderer>> ClapEditor<P> {
+    /// Creates the editor with the window the `builder` configures, for `create`.
+    ///
+    /// The window stays hidden until it's embedded with [`set_parent`], or until it's shown if
+    /// it's `is_floating`.
+    ///
+    /// [`set_parent`]: #method.set_parent
+    pub fn create(builder: ChildWindowBuilder<P>, is_floating: bool) -> Result<Self, InitError> {
+        let event_loop = EventLoop::with_user_event();
+        let child_window = builder.build_standalone(&event_loop)?;
+
+        if !is_floating {
+            child_window.window().set_visible(false);
+        }
+
+        Ok(ClapEditor {
+            child_window,
+            event_loop,
+        })
+    }
+
+    /// Embeds the editor into the `parent` of the host, for `set_parent`.
+    ///
+    /// # Safety
+    ///
+    /// `parent` must be a valid window of the host with a valid `api`, which outlives the
+    /// editor, and it must be called on the main thread.
+    pub unsafe fn set_parent(&mut self, parent: &ClapWindow) -> Result<(), EmbedError> {
+        match parent.api() {
+            #[cfg(target_os = "macos")]
+            Some(WINDOW_API_COCOA) if !parent.specific.is_null() => {
+                use cocoa::foundation::{NSPoint, NSRect, NSSize};
+
+                let size = self.get_size();
+
+                self.child_window.embed_in_nsview(
+                    parent.specific as cocoa::base::id,
+                    NSRect::new(
+                        NSPoint::new(0.0, 0.0),
+                        NSSize::new(size.width as f64, size.height as f64),
+                    ),
+                );
+
+                Ok(())
+            }
+            #[cfg(target_os = "windows")]
+            Some(WINDOW_API_WIN32) if !parent.specific.is_null() => {
+                use raw_window_handle::{windows::WindowsHandle, RawWindowHandle};
+
+                self.child_window
+                    .embed_into(RawWindowHandle::Windows(WindowsHandle {
+                        hwnd: parent.specific,
+                        ..WindowsHandle::empty()
+                    }))?;
+                self.child_window.window().set_visible(true);
+
+                Ok(())
+            }
+            _ => Err(EmbedError::UnsupportedParent),
+        }
+    }
+
+    /// The size of the editor for `get_size`: in points on macOS and in physical pixels
+    /// elsewhere, like CLAP expects.
+    pub fn get_size(&self) -> Size<u32> {
+        let window = self.child_window.window();
+        let size = window.inner_size();
+
+        if cfg!(target_os = "macos") {
+            let size = size.to_logical::<f64>(window.scale_factor());
+
+            Size::new(size.width.round() as u32, size.height.round() as u32)
+        } else {
+            Size::new(size.width, size.height)
+        }
+    }
+
+    /// Resizes the editor for `set_size`, in points on macOS and in physical pixels elsewhere.
+    ///
+    /// It's clamped to the minimal and the maximal size of the builder.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        let scale_factor = if cfg!(target_os = "macos") {
+            1.0
+        } else {
+            self.child_window.window().scale_factor()
+        };
+
+        self.child_window.resize(Size::new(
+            (width as f64 / scale_factor) as f32,
+            (height as f64 / scale_factor) as f32,
+        ));
+    }
+
+    /// Shows the editor and resumes rendering, for `show`.
+    pub fn show(&mut self) {
+        self.child_window.set_visible(true);
+    }
+
+    /// Stops rendering the editor, for `hide`, e.g. while the host hides it.
+    pub fn hide(&mut self) {
+        self.child_window.set_visible(false);
+    }
+
+    /// Destroys the editor, for `destroy`. Dropping it does the same.
+    pub fn destroy(self) {}
+
+    /// Handles the pending events, updates the program and renders a frame if it's needed.
+    /// Returns whether a frame was rendered.
+    ///
+    /// Call it from the timer or the idle callback of the host, see [`ChildWindow::tick`].
+    ///
+    /// [`ChildWindow::tick`]: ../struct.ChildWindow.html#method.tick
+    pub fn tick(&mut self) -> bool {
+        self.child_window.tick(&mut self.event_loop)
+    }
+
+    /// The window of the editor, e.g. to queue the messages of parameter changes.
+    pub fn child_window(&mut self) -> &mut ChildWindow<P> {
+        &mut self.child_window
+    }
+}
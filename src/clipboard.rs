@@ -0,0 +1,77 @@
+use iced_winit::{winit::window::Window, Clipboard};
+
+/// The clipboard the program pastes from, see [`ChildWindow::set_clipboard`].
+///
+/// The window reads the system clipboard by default. A host that routes the clipboard through
+/// its own mechanism, e.g. because it sandboxes the access to the system one, provides its own.
+/// iced doesn't copy to the clipboard yet, so only the program and the host write to it, with
+/// [`ChildWindow::clipboard`].
+///
+/// [`ChildWindow::set_clipboard`]: struct.ChildWindow.html#method.set_clipboard
+/// [`ChildWindow::clipboard`]: struct.ChildWindow.html#method.clipboard
+pub trait ClipboardProvider {
+    /// The text on the clipboard, `None` if it holds none.
+    fn read(&self) -> Option<String>;
+
+    /// Replaces the contents of the clipboard with the `text`.
+    fn write(&mut self, text: String);
+}
+
+/// A clipboard that holds nothing and ignores the writes, e.g. for headless tests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoClipboard;
+
+impl ClipboardProvider for NoClipboard {
+    fn read(&self) -> Option<String> {
+        None
+    }
+
+    fn write(&mut self, _text: String) {}
+}
+
+/// The clipboard of the system, the general `NSPasteboard` on macOS.
+pub(crate) struct SystemClipboard {
+    clipboard: Option<Clipboard>,
+}
+
+impl SystemClipboard {
+    pub(crate) fn new(window: &Window) -> Self {
+        let clipboard = Clipboard::new(window);
+
+        if clipboard.is_none() {
+            tracing::warn!("The clipboard is unavailable, pasting is disabled");
+        }
+
+        SystemClipboard { clipboard }
+    }
+}
+
+impl ClipboardProvider for SystemClipboard {
+    fn read(&self) -> Option<String> {
+        use iced_native::Clipboard as _;
+
+        self.clipboard.as_ref()?.content()
+    }
+
+    #[cfg(target_os = "macos")]
+    fn write(&mut self, text: String) {
+        unsafe { crate::cocoa_util::set_pasteboard_string(&text) };
+    }
+
+    // the clipboard of iced_winit can only be read
+    #[cfg(not(target_os = "macos"))]
+    fn write(&mut self, _text: String) {
+        tracing::warn!("Writing to the system clipboard isn't supported on this platform");
+    }
+}
+
+/// Lets iced paste from a [`ClipboardProvider`].
+///
+/// [`ClipboardProvider`]: trait.ClipboardProvider.html
+pub(crate) struct Bridge<'a>(pub(crate) &'a dyn ClipboardProvider);
+
+impl iced_native::Clipboard for Bridge<'_> {
+    fn content(&self) -> Option<String> {
+        self.0.read()
+    }
+}
@@ -0,0 +1,881 @@
+//! Thin wrappers around the cocoa calls we embed with.
+//!
+//! All of them message objects, which can't be checked on our side, so they stay `unsafe`. The
+//! invariants are the same for each of them:
+//!
+//! - every `id` is a valid, retained object of the documented class,
+//! - they are called on the main thread, like any AppKit call.
+//!
+//! [`wake_main_run_loop`] is the exception, it's safe to call from any thread.
+//!
+//! [`wake_main_run_loop`]: fn.wake_main_run_loop.html
+
+use std::ffi::{CStr, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+use cocoa::{
+    appkit::{
+        NSApp, NSBackingStoreType, NSFilenamesPboardType, NSPasteboardTypeString, NSView, NSWindow,
+        NSWindowStyleMask,
+    },
+    base::{id, nil, BOOL, NO, YES},
+    foundation::{
+        NSArray, NSAutoreleasePool, NSInteger, NSPoint, NSRect, NSSize, NSString, NSUInteger,
+    },
+};
+use objc::{
+    class,
+    declare::ClassDecl,
+    msg_send,
+    runtime::{Class, Object, Sel},
+    sel, sel_impl,
+};
+
+use crate::MetalLayerOptions;
+
+type CFRunLoopRef = *mut std::ffi::c_void;
+type CFStringRef = *const std::ffi::c_void;
+type CGColorSpaceRef = *mut std::ffi::c_void;
+
+/// An `IOSurfaceRef`, see [`create_iosurface`].
+///
+/// [`create_iosurface`]: fn.create_iosurface.html
+#[cfg(feature = "iosurface")]
+pub type IOSurfaceRef = *mut std::ffi::c_void;
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFRunLoopGetMain() -> CFRunLoopRef;
+    fn CFRunLoopWakeUp(run_loop: CFRunLoopRef);
+    #[cfg(feature = "iosurface")]
+    fn CFRelease(cf: *const std::ffi::c_void);
+}
+
+/// The `'BGRA'` pixel format of the IOSurfaces we create.
+#[cfg(feature = "iosurface")]
+const BGRA_PIXEL_FORMAT: u32 = 0x4247_5241;
+
+#[cfg(feature = "iosurface")]
+#[link(name = "IOSurface", kind = "framework")]
+extern "C" {
+    static kIOSurfaceWidth: CFStringRef;
+    static kIOSurfaceHeight: CFStringRef;
+    static kIOSurfaceBytesPerElement: CFStringRef;
+    static kIOSurfacePixelFormat: CFStringRef;
+
+    // the dictionary is toll-free bridged
+    fn IOSurfaceCreate(properties: id) -> IOSurfaceRef;
+    fn IOSurfaceLock(buffer: IOSurfaceRef, options: u32, seed: *mut u32) -> i32;
+    fn IOSurfaceUnlock(buffer: IOSurfaceRef, options: u32, seed: *mut u32) -> i32;
+    fn IOSurfaceGetBaseAddress(buffer: IOSurfaceRef) -> *mut u8;
+    fn IOSurfaceGetBytesPerRow(buffer: IOSurfaceRef) -> usize;
+}
+
+#[link(name = "objc")]
+extern "C" {
+    fn object_getClass(object: id) -> *const Class;
+    fn object_setClass(object: id, class: *const Class) -> *const Class;
+}
+
+/// The `kCGErrorSuccess` of `CGError`.
+const K_CG_ERROR_SUCCESS: i32 = 0;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    // CGPoint has the layout of NSPoint
+    fn CGWarpMouseCursorPosition(new_cursor_position: NSPoint) -> i32;
+
+    static kCGColorSpaceExtendedLinearSRGB: CFStringRef;
+
+    fn CGColorSpaceCreateWithName(name: CFStringRef) -> CGColorSpaceRef;
+    fn CGColorSpaceRelease(space: CGColorSpaceRef);
+}
+
+/// The decorations of a window created with [`create_window`].
+///
+/// [`create_window`]: fn.create_window.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decorations {
+    /// No title bar, the content fills the window.
+    None,
+    /// A title bar, the size is set by the program.
+    Titled,
+    /// A title bar and resizable edges. Poll the [`ParentSurface::frame`] to resize the child.
+    ///
+    /// [`ParentSurface::frame`]: ../trait.ParentSurface.html#tymethod.frame
+    TitledResizable,
+}
+
+impl Decorations {
+    /// The style mask of the window.
+    ///
+    /// The content rect excludes the title bar, so the content view, which hosts the child, is
+    /// offset by it in the titled windows.
+    pub fn style_mask(self) -> NSWindowStyleMask {
+        match self {
+            Decorations::None => NSWindowStyleMask::NSBorderlessWindowMask,
+            Decorations::Titled => NSWindowStyleMask::NSTitledWindowMask,
+            Decorations::TitledResizable => {
+                NSWindowStyleMask::NSTitledWindowMask | NSWindowStyleMask::NSResizableWindowMask
+            }
+        }
+    }
+}
+
+/// Wakes the run loop of the main thread, so winit runs an iteration of the event loop.
+///
+/// Unlike the other functions, it can be called from any thread.
+pub fn wake_main_run_loop() {
+    unsafe { CFRunLoopWakeUp(CFRunLoopGetMain()) };
+}
+
+/// Creates a buffered `NSWindow` with the content `frame`.
+///
+/// # Safety
+///
+/// It must be called on the main thread. The window is released when it's closed with
+/// [`close_window`].
+///
+/// [`close_window`]: fn.close_window.html
+pub unsafe fn create_window(frame: NSRect, decorations: Decorations) -> id {
+    NSWindow::alloc(nil).initWithContentRect_styleMask_backing_defer_(
+        frame,
+        decorations.style_mask(),
+        NSBackingStoreType::NSBackingStoreBuffered,
+        NO,
+    )
+}
+
+/// Makes the `window` receive the mouse moved events, which hover effects need.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn set_accepts_mouse_moved(window: id, accepts: bool) {
+    window.setAcceptsMouseMovedEvents_(to_bool(accepts));
+}
+
+/// Brings the `window` to the front of its level without making it key.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn order_front(window: id) {
+    window.orderFront_(nil);
+}
+
+/// Raises the `window` to the floating level, above the normal windows, or lowers it back to
+/// the normal level.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn set_floating(window: id, floating: bool) {
+    // NSFloatingWindowLevel and NSNormalWindowLevel
+    let level: NSInteger = if floating { 3 } else { 0 };
+
+    let () = msg_send![window, setLevel: level];
+}
+
+/// Moves and resizes the `window` to the screen `frame`.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn set_window_frame(window: id, frame: NSRect) {
+    window.setFrame_display_(frame, YES);
+}
+
+/// Sets the title of the `window`.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn set_window_title(window: id, title: &str) {
+    // the string is created from the UTF-8 bytes, so any title works
+    window.setTitle_(ns_string(title));
+}
+
+/// Sets the minimal size of the content of the `window`, which the user can resize it to.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn set_content_min_size(window: id, size: NSSize) {
+    window.setContentMinSize_(size);
+}
+
+/// Sets the maximal size of the content of the `window`, which the user can resize it to.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn set_content_max_size(window: id, size: NSSize) {
+    window.setContentMaxSize_(size);
+}
+
+/// Closes the `window`, which releases it.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow`, which isn't used afterwards, and it must be called on the
+/// main thread.
+pub unsafe fn close_window(window: id) {
+    window.close();
+}
+
+/// The bounds of the content view of the `window`, which excludes the title bar.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn content_bounds(window: id) -> NSRect {
+    NSView::bounds(window.contentView())
+}
+
+/// Adds the `child` view to the `parent` view, filling its bounds.
+///
+/// # Safety
+///
+/// `parent` and `child` must be valid `NSView`s and it must be called on the main thread. The
+/// parent retains the child until it's removed with [`detach_view`].
+///
+/// [`detach_view`]: fn.detach_view.html
+pub unsafe fn attach_subview(parent: id, child: id) {
+    attach_subview_at(parent, child, NSView::bounds(parent));
+}
+
+/// Adds the `child` view to the `parent` view at the `frame` in the bounds of the parent.
+///
+/// A child in another superview is moved.
+///
+/// # Safety
+///
+/// `parent` and `child` must be valid `NSView`s and it must be called on the main thread. The
+/// parent retains the child until it's removed with [`detach_view`].
+///
+/// [`detach_view`]: fn.detach_view.html
+pub unsafe fn attach_subview_at(parent: id, child: id, frame: NSRect) {
+    set_view_frame(child, frame);
+    parent.addSubview_(child);
+}
+
+/// Removes the `view` from its superview, which releases it.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+pub unsafe fn detach_view(view: id) {
+    NSView::removeFromSuperview(view);
+}
+
+/// Moves and resizes the `view` to the `frame` in the coordinates of its superview.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+pub unsafe fn set_view_frame(view: id, frame: NSRect) {
+    NSView::setFrameOrigin(view, frame.origin);
+    NSView::setFrameSize(view, frame.size);
+}
+
+/// Resizes the `view`, keeping its origin in its superview.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+pub unsafe fn set_view_size(view: id, size: NSSize) {
+    NSView::setFrameSize(view, size);
+}
+
+/// The window hosting the `view`, if it's in one.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+pub unsafe fn view_window(view: id) -> Option<id> {
+    let window: id = msg_send![view, window];
+
+    if window == nil {
+        None
+    } else {
+        Some(window)
+    }
+}
+
+/// The `occlusionState` of the `window`.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn occlusion_state(window: id) -> NSUInteger {
+    msg_send![window, occlusionState]
+}
+
+/// Makes the `view` the first responder of the `window`.
+///
+/// Returns whether the `window` has accepted it.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow`, `view` must be a valid `NSView` in it, and it must be
+/// called on the main thread.
+pub unsafe fn make_first_responder(window: id, view: id) -> bool {
+    let accepted: BOOL = msg_send![window, makeFirstResponder: view];
+
+    accepted != NO
+}
+
+/// Makes the `window` of the `view` update the cursor of the view.
+///
+/// winit invalidates the cursor rects in the window it has created the view in, which isn't the
+/// one hosting it once it's attached to a parent.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+pub unsafe fn invalidate_cursor_rects(view: id) {
+    if let Some(window) = view_window(view) {
+        let () = msg_send![window, invalidateCursorRectsForView: view];
+    }
+}
+
+/// Moves the mouse cursor to the `position` in the `view`, measured from its top left corner
+/// in points.
+///
+/// Unlike winit, it finds the position on the screen through the window actually hosting the
+/// view, offset by its title bar, and it doesn't release a grabbed cursor. Returns whether the
+/// cursor has moved.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+pub unsafe fn warp_cursor(view: id, position: NSPoint) -> bool {
+    let window = match view_window(view) {
+        Some(window) => window,
+        None => return false,
+    };
+
+    // the view isn't flipped, its origin is at the bottom left
+    let bounds = NSView::bounds(view);
+    let in_view = NSPoint::new(position.x, bounds.size.height - position.y);
+    let in_window: NSPoint = msg_send![view, convertPoint: in_view toView: nil];
+    let on_screen: NSRect = msg_send![
+        window,
+        convertRectToScreen: NSRect::new(in_window, NSSize::new(0.0, 0.0))
+    ];
+
+    // Quartz measures from the top left corner of the main screen, AppKit from the bottom left
+    let screens: id = msg_send![class!(NSScreen), screens];
+    let main_screen: id = msg_send![screens, objectAtIndex: 0 as NSUInteger];
+    let main_frame: NSRect = msg_send![main_screen, frame];
+    let position = NSPoint::new(
+        on_screen.origin.x,
+        main_frame.size.height - on_screen.origin.y,
+    );
+
+    CGWarpMouseCursorPosition(position) == K_CG_ERROR_SUCCESS
+}
+
+/// Maps the `position` in the `view`, measured from its top left corner in points, to the
+/// content view of the window hosting it, measured from its top left corner too.
+///
+/// winit positions the IME candidate window in the content of the window, which is offset from
+/// the view once it's attached to a parent. The `position` is kept if the view isn't in a window.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+pub unsafe fn position_in_content(view: id, position: NSPoint) -> NSPoint {
+    let window = match view_window(view) {
+        Some(window) => window,
+        None => return position,
+    };
+
+    // the views aren't flipped, their origin is at the bottom left
+    let bounds = NSView::bounds(view);
+    let in_view = NSPoint::new(position.x, bounds.size.height - position.y);
+    let in_window: NSPoint = msg_send![view, convertPoint: in_view toView: nil];
+
+    NSPoint::new(
+        in_window.x,
+        content_bounds(window).size.height - in_window.y,
+    )
+}
+
+/// The maximal interval in seconds between the clicks of a double-click, which the user sets.
+///
+/// # Safety
+///
+/// It must be called on the main thread.
+pub unsafe fn double_click_interval() -> f64 {
+    msg_send![class!(NSEvent), doubleClickInterval]
+}
+
+/// The backing scale factor of the `window`, 2 on Retina displays.
+///
+/// # Safety
+///
+/// `window` must be a valid `NSWindow` and it must be called on the main thread.
+pub unsafe fn backing_scale_factor(window: id) -> f64 {
+    NSWindow::backingScaleFactor(window)
+}
+
+/// Sets the scale of the contents of the layer backing the `view`, which maps the pixels it's
+/// rendered in to points.
+///
+/// # Safety
+///
+/// `view` must be a valid layer-backed `NSView` and it must be called on the main thread.
+pub unsafe fn set_layer_contents_scale(view: id, scale: f64) {
+    let layer: id = msg_send![view, layer];
+    let () = msg_send![layer, setContentsScale: scale];
+}
+
+/// Makes the `CAMetalLayer` backing the `view` interpret its contents as extended linear sRGB,
+/// so colors brighter than SDR white are displayed with headroom, or reverts it to the
+/// untagged sRGB it has by default.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` backed by a `CAMetalLayer` and it must be called on the main
+/// thread.
+pub unsafe fn set_layer_extended_range(view: id, extended_range: bool) {
+    let layer: id = msg_send![view, layer];
+
+    if extended_range {
+        let color_space = CGColorSpaceCreateWithName(kCGColorSpaceExtendedLinearSRGB);
+        let () = msg_send![layer, setColorspace: color_space];
+        // the layer retains it
+        CGColorSpaceRelease(color_space);
+    } else {
+        let () = msg_send![layer, setColorspace: std::ptr::null_mut::<std::ffi::c_void>()];
+    }
+
+    let () = msg_send![layer, setWantsExtendedDynamicRangeContent: to_bool(extended_range)];
+}
+
+/// Creates an autoreleased `NSAccessibilityElement` of the `role`, e.g. `AXButton`, in the
+/// `parent` view at the `frame` in its bounds.
+///
+/// # Safety
+///
+/// `parent` must be a valid `NSView` and it must be called on the main thread, on macOS 10.10 or
+/// newer.
+pub unsafe fn create_accessibility_element(
+    parent: id,
+    role: &str,
+    label: &str,
+    value: Option<&str>,
+    frame: NSRect,
+) -> id {
+    let element: id = msg_send![class!(NSAccessibilityElement), new];
+    let element = element.autorelease();
+
+    let () = msg_send![element, setAccessibilityRole: ns_string(role)];
+    let () = msg_send![element, setAccessibilityLabel: ns_string(label)];
+
+    if let Some(value) = value {
+        let () = msg_send![element, setAccessibilityValue: ns_string(value)];
+    }
+
+    let () = msg_send![element, setAccessibilityParent: parent];
+    let () = msg_send![element, setAccessibilityFrameInParentSpace: frame];
+
+    element
+}
+
+/// Replaces the accessibility children of the `view` with the `children`, which it retains.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView`, the `children` valid accessibility elements, and it must be
+/// called on the main thread, on macOS 10.10 or newer.
+pub unsafe fn set_accessibility_children(view: id, children: &[id]) {
+    let children = NSArray::arrayWithObjects(nil, children);
+    let () = msg_send![view, setAccessibilityChildren: children];
+}
+
+/// Hides or shows the `view` and its subviews in its superview.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+pub unsafe fn set_view_hidden(view: id, hidden: bool) {
+    let () = msg_send![view, setHidden: to_bool(hidden)];
+}
+
+/// Replaces the contents of the general pasteboard with the `text`.
+///
+/// # Safety
+///
+/// It must be called on the main thread.
+pub unsafe fn set_pasteboard_string(text: &str) {
+    let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+
+    let _: NSInteger = msg_send![pasteboard, clearContents];
+    let _: BOOL = msg_send![pasteboard, setString: ns_string(text) forType: NSPasteboardTypeString];
+}
+
+/// Makes the layer backing the `view` transparent or opaque.
+///
+/// # Safety
+///
+/// `view` must be a valid layer-backed `NSView` and it must be called on the main thread.
+pub unsafe fn set_layer_opaque(view: id, opaque: bool) {
+    let layer: id = msg_send![view, layer];
+    let () = msg_send![layer, setOpaque: to_bool(opaque)];
+}
+
+/// Sets the properties of the `CAMetalLayer` backing the `view` that the `options` set, and
+/// keeps the others.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` backed by a `CAMetalLayer` and it must be called on the main
+/// thread.
+pub unsafe fn configure_metal_layer(view: id, options: &MetalLayerOptions) {
+    let layer: id = msg_send![view, layer];
+
+    if let Some(framebuffer_only) = options.framebuffer_only {
+        let () = msg_send![layer, setFramebufferOnly: to_bool(framebuffer_only)];
+    }
+
+    if let Some(display_sync_enabled) = options.display_sync_enabled {
+        let () = msg_send![layer, setDisplaySyncEnabled: to_bool(display_sync_enabled)];
+    }
+
+    if let Some(maximum_drawable_count) = options.maximum_drawable_count {
+        // Core Animation raises an exception for any other count
+        let count = maximum_drawable_count.max(2).min(3) as NSUInteger;
+        let () = msg_send![layer, setMaximumDrawableCount: count];
+    }
+
+    if let Some(presents_with_transaction) = options.presents_with_transaction {
+        let () = msg_send![
+            layer,
+            setPresentsWithTransaction: to_bool(presents_with_transaction)
+        ];
+    }
+}
+
+/// Creates an `IOSurface` of `width`×`height` BGRA pixels, `None` if it can't be created, e.g.
+/// for a zero size. Release it with [`release_iosurface`].
+///
+/// # Safety
+///
+/// It must be called with an autorelease pool, like any AppKit call on the main thread.
+///
+/// [`release_iosurface`]: fn.release_iosurface.html
+#[cfg(feature = "iosurface")]
+pub unsafe fn create_iosurface(width: u32, height: u32) -> Option<IOSurfaceRef> {
+    let number = |value: u32| -> id { msg_send![class!(NSNumber), numberWithUnsignedInt: value] };
+
+    let keys = [
+        kIOSurfaceWidth as id,
+        kIOSurfaceHeight as id,
+        kIOSurfaceBytesPerElement as id,
+        kIOSurfacePixelFormat as id,
+    ];
+    let values = [
+        number(width),
+        number(height),
+        number(4),
+        number(BGRA_PIXEL_FORMAT),
+    ];
+    let properties: id = msg_send![
+        class!(NSDictionary),
+        dictionaryWithObjects: values.as_ptr()
+        forKeys: keys.as_ptr()
+        count: keys.len() as NSUInteger
+    ];
+
+    let surface = IOSurfaceCreate(properties);
+
+    if surface.is_null() {
+        None
+    } else {
+        Some(surface)
+    }
+}
+
+/// Writes the tightly packed RGBA rows of `pixels` into the BGRA `surface` of `width`×`height`
+/// pixels, which the host may be reading from at the same time, so it's locked meanwhile.
+///
+/// # Safety
+///
+/// `surface` must be a valid surface of [`create_iosurface`] of the same size, and `pixels`
+/// must hold `width * height` pixels.
+///
+/// [`create_iosurface`]: fn.create_iosurface.html
+#[cfg(feature = "iosurface")]
+pub unsafe fn write_iosurface(surface: IOSurfaceRef, pixels: &[u8], width: u32, height: u32) {
+    let (width, height) = (width as usize, height as usize);
+    assert!(pixels.len() >= width * height * 4);
+
+    if IOSurfaceLock(surface, 0, std::ptr::null_mut()) != 0 {
+        return;
+    }
+
+    let base = IOSurfaceGetBaseAddress(surface);
+    let bytes_per_row = IOSurfaceGetBytesPerRow(surface);
+
+    for (y, row) in pixels.chunks(width * 4).take(height).enumerate() {
+        let destination = std::slice::from_raw_parts_mut(base.add(y * bytes_per_row), width * 4);
+
+        for (destination, source) in destination.chunks_mut(4).zip(row.chunks(4)) {
+            destination.copy_from_slice(&[source[2], source[1], source[0], source[3]]);
+        }
+    }
+
+    IOSurfaceUnlock(surface, 0, std::ptr::null_mut());
+}
+
+/// Releases a `surface` of [`create_iosurface`]. The host keeps it alive if it has retained it.
+///
+/// # Safety
+///
+/// `surface` must be a valid surface of [`create_iosurface`] which isn't used after.
+///
+/// [`create_iosurface`]: fn.create_iosurface.html
+#[cfg(feature = "iosurface")]
+pub unsafe fn release_iosurface(surface: IOSurfaceRef) {
+    CFRelease(surface);
+}
+
+/// The class of the `object`, which may differ from the one it was allocated with.
+///
+/// # Safety
+///
+/// `object` must be a valid object.
+pub unsafe fn object_class(object: id) -> &'static Class {
+    &*object_getClass(object)
+}
+
+/// Swaps the class of the `view` for the `class` and returns the previous one.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView`, `class` its class or a subclass of it without instance
+/// variables of its own, and it must be called on the main thread.
+pub unsafe fn swap_view_class(view: id, class: &'static Class) -> &'static Class {
+    &*object_setClass(view, class)
+}
+
+/// Declares the subclass `name` of the view class `superclass`, which implements the
+/// `draggingEntered:`, `draggingExited:` and `performDragOperation:` of a dragging destination
+/// with the functions of the same names.
+///
+/// Returns `None` if a class of the `name` exists already.
+///
+/// # Safety
+///
+/// `superclass` must be a subclass of `NSView` and it must be called on the main thread.
+pub unsafe fn declare_drop_target_class(
+    name: &str,
+    superclass: &'static Class,
+    dragging_entered: extern "C" fn(&Object, Sel, id) -> NSUInteger,
+    dragging_exited: extern "C" fn(&Object, Sel, id),
+    perform_drag_operation: extern "C" fn(&Object, Sel, id) -> BOOL,
+) -> Option<&'static Class> {
+    let mut decl = ClassDecl::new(name, superclass)?;
+
+    decl.add_method(sel!(draggingEntered:), dragging_entered);
+    decl.add_method(sel!(draggingExited:), dragging_exited);
+    decl.add_method(sel!(performDragOperation:), perform_drag_operation);
+
+    Some(decl.register())
+}
+
+/// Registers the `view` as a dragging destination of files.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+pub unsafe fn register_file_drops(view: id) {
+    let types = NSArray::arrayWithObjects(nil, &[NSFilenamesPboardType]);
+    let () = msg_send![view, registerForDraggedTypes: types];
+}
+
+/// Unregisters the `view` as a dragging destination, see [`register_file_drops`].
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView` and it must be called on the main thread.
+///
+/// [`register_file_drops`]: fn.register_file_drops.html
+pub unsafe fn unregister_file_drops(view: id) {
+    let () = msg_send![view, unregisterDraggedTypes];
+}
+
+/// The paths of the files dragged with the `dragging_info`, empty if it drags no files.
+///
+/// # Safety
+///
+/// `dragging_info` must be a valid `NSDraggingInfo` and it must be called on the main thread.
+pub unsafe fn dragged_filenames(dragging_info: id) -> Vec<PathBuf> {
+    let pasteboard: id = msg_send![dragging_info, draggingPasteboard];
+    let filenames: id = msg_send![pasteboard, propertyListForType: NSFilenamesPboardType];
+
+    if filenames == nil {
+        return Vec::new();
+    }
+
+    (0..filenames.count())
+        .map(|index| {
+            let filename = filenames.objectAtIndex(index);
+            let path = CStr::from_ptr(filename.UTF8String()).to_bytes();
+
+            PathBuf::from(OsStr::from_bytes(path))
+        })
+        .collect()
+}
+
+/// The `NSGestureRecognizerStateBegan` of `NSGestureRecognizerState`.
+const NS_GESTURE_RECOGNIZER_STATE_BEGAN: NSInteger = 1;
+
+/// Adds an `NSMagnificationGestureRecognizer` to the `view`, which sends `action` to the
+/// `target` on each step of a pinch, and returns it. The view retains it.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView`, `target` a valid object responding to `action` that
+/// outlives the recognizer, and it must be called on the main thread.
+pub unsafe fn add_magnification_recognizer(view: id, target: id, action: Sel) -> id {
+    let recognizer: id = msg_send![class!(NSMagnificationGestureRecognizer), alloc];
+    let recognizer: id = msg_send![recognizer, initWithTarget: target action: action];
+    let recognizer = recognizer.autorelease();
+
+    let () = msg_send![view, addGestureRecognizer: recognizer];
+
+    recognizer
+}
+
+/// Removes the gesture `recognizer` from the `view`, which releases it.
+///
+/// # Safety
+///
+/// `view` must be a valid `NSView`, `recognizer` one of its recognizers, and it must be called
+/// on the main thread.
+pub unsafe fn remove_gesture_recognizer(view: id, recognizer: id) {
+    let () = msg_send![view, removeGestureRecognizer: recognizer];
+}
+
+/// Whether the pinch of the magnification `recognizer` has just begun, its magnification so
+/// far and the position of the pinch in its view, in points from the top left corner.
+///
+/// # Safety
+///
+/// `recognizer` must be a valid `NSMagnificationGestureRecognizer` in a view and it must be
+/// called on the main thread.
+pub unsafe fn magnification(recognizer: id) -> (bool, f64, NSPoint) {
+    let state: NSInteger = msg_send![recognizer, state];
+    let magnification: f64 = msg_send![recognizer, magnification];
+
+    // the views aren't flipped, their origin is at the bottom left
+    let view: id = msg_send![recognizer, view];
+    let position: NSPoint = msg_send![recognizer, locationInView: view];
+    let bounds = NSView::bounds(view);
+
+    (
+        state == NS_GESTURE_RECOGNIZER_STATE_BEGAN,
+        magnification,
+        NSPoint::new(position.x, bounds.size.height - position.y),
+    )
+}
+
+/// Declares the subclass `name` of `NSObject`, which implements the `action` of a target, e.g.
+/// of a gesture recognizer, with the `method`.
+///
+/// Returns `None` if a class of the `name` exists already.
+///
+/// # Safety
+///
+/// It must be called on the main thread.
+pub unsafe fn declare_target_class(
+    name: &str,
+    action: Sel,
+    method: extern "C" fn(&Object, Sel, id),
+) -> Option<&'static Class> {
+    let mut decl = ClassDecl::new(name, class!(NSObject))?;
+
+    decl.add_method(action, method);
+
+    Some(decl.register())
+}
+
+/// Creates a retained instance of the `class`, which is released with [`release`].
+///
+/// # Safety
+///
+/// `class` must be a subclass of `NSObject` and it must be called on the main thread.
+///
+/// [`release`]: fn.release.html
+pub unsafe fn create_object(class: &Class) -> id {
+    msg_send![class, new]
+}
+
+/// Releases the `object`.
+///
+/// # Safety
+///
+/// `object` must be a valid object retained by the caller and it must be called on the main
+/// thread.
+pub unsafe fn release(object: id) {
+    let () = msg_send![object, release];
+}
+
+/// The name of the effective appearance of the application, e.g. `NSAppearanceNameDarkAqua`.
+///
+/// It's `None` without an application, before macOS 10.14, which introduced the dark mode, or
+/// if the appearance has no name.
+///
+/// # Safety
+///
+/// It must be called on the main thread.
+pub unsafe fn effective_appearance_name() -> Option<String> {
+    let app = NSApp();
+
+    if app == nil {
+        return None;
+    }
+
+    let has_appearance: BOOL = msg_send![app, respondsToSelector: sel!(effectiveAppearance)];
+
+    if has_appearance == NO {
+        return None;
+    }
+
+    let appearance: id = msg_send![app, effectiveAppearance];
+
+    if appearance == nil {
+        return None;
+    }
+
+    let name: id = msg_send![appearance, name];
+    let name = if name == nil {
+        std::ptr::null()
+    } else {
+        name.UTF8String()
+    };
+
+    if name.is_null() {
+        return None;
+    }
+
+    Some(CStr::from_ptr(name).to_string_lossy().into_owned())
+}
+
+/// An autoreleased copy of the `string`.
+unsafe fn ns_string(string: &str) -> id {
+    NSString::alloc(nil).init_str(string).autorelease()
+}
+
+fn to_bool(value: bool) -> BOOL {
+    if value {
+        YES
+    } else {
+        NO
+    }
+}
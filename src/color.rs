@@ -0,0 +1,48 @@
+use iced_wgpu::wgpu;
+use iced_winit::Color;
+
+/// Converts the sRGB `color` used by iced into the linear clear color of a render pass, e.g.
+/// for [`ChildWindow::set_clear_color`].
+///
+/// The swap chain formats are `*Srgb` on every backend unless forced otherwise, so wgpu encodes
+/// the clear color to sRGB when it writes it, like it encodes the linear colors iced's shaders
+/// output. Cleared with the converted color, the background matches an iced background of the
+/// same `color`. The alpha is linear already and stays as it is.
+///
+/// On a linear format, selected with [`ChildWindowBuilder::force_srgb`] or as the last resort,
+/// wgpu writes both unencoded, so they still match.
+///
+/// [`ChildWindowBuilder::force_srgb`]: struct.ChildWindowBuilder.html#method.force_srgb
+/// [`ChildWindow::set_clear_color`]: struct.ChildWindow.html#method.set_clear_color
+pub fn linear_color(color: Color) -> wgpu::Color {
+    let [r, g, b, a] = color.into_linear();
+
+    wgpu::Color {
+        r: r as f64,
+        g: g as f64,
+        b: b as f64,
+        a: a as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_black_and_white() {
+        assert_eq!(linear_color(Color::BLACK), wgpu::Color::BLACK);
+        assert_eq!(linear_color(Color::WHITE), wgpu::Color::WHITE);
+    }
+
+    #[test]
+    fn decodes_the_color_but_not_the_alpha() {
+        let color = linear_color(Color::from_rgba(0.5, 0.5, 0.5, 0.5));
+
+        // the sRGB transfer function, ((0.5 + 0.055) / 1.055) ^ 2.4
+        assert!((color.r - 0.2140).abs() < 1e-4);
+        assert_eq!(color.g, color.r);
+        assert_eq!(color.b, color.r);
+        assert_eq!(color.a, 0.5);
+    }
+}
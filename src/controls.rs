@@ -1,25 +1,137 @@
 use iced_wgpu::Renderer;
 use iced_winit::{
-    slider, Align, Color, Column, Command, Element, Length, Program, Row, Slider, Text,
+    slider, winit::event::VirtualKeyCode, Align, Color, Column, Command, Element, Length, Program,
+    Row, Slider, Text,
 };
 
+use crate::{AccessibilityNode, AccessibilityRole, Theme};
+
 pub struct Controls {
     amp: f32,
     slider: slider::State,
+    background_color: Color,
+    background_sliders: [slider::State; 3],
+    theme: Theme,
+    // the index of the focused slider, the amp one and then the red, green and blue ones. iced
+    // doesn't track the focus of sliders
+    focused: Option<usize>,
+}
+
+/// The state of the controls a host persists while the editor is closed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControlsState {
+    pub amp: f32,
+    #[cfg_attr(feature = "json", serde(with = "color_components"))]
+    pub background_color: Color,
+}
+
+#[cfg(feature = "json")]
+impl ControlsState {
+    /// Serializes the state, e.g. into the preset blob of a plugin.
+    ///
+    /// The floats are written in their shortest representation that parses back to the same
+    /// value, so the colors round-trip exactly.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Serializes a `Color` as its `[r, g, b, a]` components, iced doesn't implement serde.
+#[cfg(feature = "json")]
+mod color_components {
+    use iced_winit::Color;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        [color.r, color.g, color.b, color.a].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let [r, g, b, a] = <[f32; 4]>::deserialize(deserializer)?;
+
+        Ok(Color { r, g, b, a })
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Message {
     AmpChanged(f32),
+    BackgroundColorChanged(Color),
+    ThemeChanged(Theme),
+    /// Tab was pressed, `true` with Shift.
+    FocusMoved(bool),
+    /// An arrow key was pressed, the focused slider moves by the step.
+    FocusedSliderStepped(f32),
 }
 
+/// The sliders the focus moves between.
+const FOCUSABLE_SLIDERS: usize = 4;
+
+/// How far the arrow keys move a slider.
+const STEP: f32 = 0.05;
+
+/// The names of the background sliders.
+const COMPONENTS: [&str; 3] = ["red", "green", "blue"];
+
 impl Controls {
     pub fn new() -> Controls {
         Controls {
             amp: 0.0,
             slider: Default::default(),
+            background_color: Color::from_rgb(1.0, 0.5, 0.0),
+            background_sliders: Default::default(),
+            theme: Theme::Dark,
+            focused: None,
+        }
+    }
+
+    pub fn background_color(&self) -> Color {
+        self.background_color
+    }
+
+    /// Steps the focused slider with the arrow keys, see `ChildWindow::set_key_message`.
+    pub fn key_message(key: VirtualKeyCode) -> Option<Message> {
+        match key {
+            VirtualKeyCode::Left | VirtualKeyCode::Down => {
+                Some(Message::FocusedSliderStepped(-STEP))
+            }
+            VirtualKeyCode::Right | VirtualKeyCode::Up => Some(Message::FocusedSliderStepped(STEP)),
+            _ => None,
         }
     }
+
+    pub fn accessibility(&self) -> Vec<AccessibilityNode> {
+        let slider = |label: &str, value: f32| AccessibilityNode {
+            role: AccessibilityRole::Slider,
+            label: label.to_owned(),
+            value: Some(format!("{:.2}", value)),
+            bounds: None,
+        };
+
+        vec![
+            slider("Amp", self.amp),
+            slider("Background red", self.background_color.r),
+            slider("Background green", self.background_color.g),
+            slider("Background blue", self.background_color.b),
+        ]
+    }
+
+    pub fn snapshot_state(&self) -> ControlsState {
+        ControlsState {
+            amp: self.amp,
+            background_color: self.background_color,
+        }
+    }
+
+    pub fn restore_state(&mut self, state: ControlsState) {
+        self.amp = state.amp;
+        self.background_color = state.background_color;
+    }
 }
 
 impl Program for Controls {
@@ -27,8 +139,37 @@ impl Program for Controls {
     type Message = Message;
 
     fn update(&mut self, message: Message) -> Command<Message> {
-        let Message::AmpChanged(amp) = message;
-        self.amp = amp;
+        match message {
+            Message::AmpChanged(amp) => {
+                self.amp = amp;
+            }
+            Message::BackgroundColorChanged(color) => {
+                self.background_color = color;
+            }
+            Message::ThemeChanged(theme) => {
+                self.theme = theme;
+            }
+            Message::FocusMoved(backward) => {
+                self.focused = Some(match (self.focused, backward) {
+                    (None, false) => 0,
+                    (None, true) => FOCUSABLE_SLIDERS - 1,
+                    (Some(slider), false) => (slider + 1) % FOCUSABLE_SLIDERS,
+                    (Some(slider), true) => (slider + FOCUSABLE_SLIDERS - 1) % FOCUSABLE_SLIDERS,
+                });
+            }
+            Message::FocusedSliderStepped(step) => {
+                let step = |value: f32| (value + step).max(0.0).min(1.0);
+                let color = &mut self.background_color;
+
+                match self.focused {
+                    Some(0) => self.amp = step(self.amp),
+                    Some(1) => color.r = step(color.r),
+                    Some(2) => color.g = step(color.g),
+                    Some(3) => color.b = step(color.b),
+                    _ => {}
+                }
+            }
+        }
 
         Command::none()
     }
@@ -44,6 +185,43 @@ impl Program for Controls {
                 move |r| Message::AmpChanged(r),
             ));
 
+        let [r, g, b] = &mut self.background_sliders;
+        let background_color = self.background_color;
+        let text_color = match self.theme {
+            Theme::Light => Color::BLACK,
+            Theme::Dark => Color::WHITE,
+        };
+        let amp_label = match self.focused {
+            Some(0) => "> Amp".to_owned(),
+            _ => "Amp".to_owned(),
+        };
+        let background_label = match self.focused {
+            Some(slider @ 1..=3) => format!("> Background color: {}", COMPONENTS[slider - 1]),
+            _ => "Background color".to_owned(),
+        };
+
+        let background_sliders = Row::new()
+            .width(Length::Units(500))
+            .spacing(20)
+            .push(Slider::new(r, 0.0..=1.0, background_color.r, move |r| {
+                Message::BackgroundColorChanged(Color {
+                    r,
+                    ..background_color
+                })
+            }))
+            .push(Slider::new(g, 0.0..=1.0, background_color.g, move |g| {
+                Message::BackgroundColorChanged(Color {
+                    g,
+                    ..background_color
+                })
+            }))
+            .push(Slider::new(b, 0.0..=1.0, background_color.b, move |b| {
+                Message::BackgroundColorChanged(Color {
+                    b,
+                    ..background_color
+                })
+            }));
+
         Row::new()
             .width(Length::Fill)
             .height(Length::Fill)
@@ -54,10 +232,91 @@ impl Program for Controls {
                     .align_items(Align::Center)
                     .padding(10)
                     .spacing(10)
-                    .push(Text::new("Amp").color(Color::WHITE))
+                    .push(Text::new(amp_label).color(text_color))
                     .push(slider)
-                    .push(Text::new(format!("{:.2}", self.amp)).color(Color::WHITE)),
+                    .push(Text::new(format!("{:.2}", self.amp)).color(text_color))
+                    .push(Text::new(background_label).color(text_color))
+                    .push(background_sliders)
+                    .push(
+                        Text::new(format!("{:?}", background_color))
+                            .size(14)
+                            .color(text_color),
+                    ),
             )
             .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(controls: &mut Controls, key: VirtualKeyCode) {
+        let message = match key {
+            VirtualKeyCode::Tab => Some(Message::FocusMoved(false)),
+            key => Controls::key_message(key),
+        };
+
+        if let Some(message) = message {
+            let _ = controls.update(message);
+        }
+    }
+
+    #[test]
+    fn the_arrow_keys_move_the_focused_slider() {
+        let mut controls = Controls::new();
+
+        press(&mut controls, VirtualKeyCode::Tab);
+        press(&mut controls, VirtualKeyCode::Right);
+        press(&mut controls, VirtualKeyCode::Up);
+        assert!((controls.amp - 2.0 * STEP).abs() < 1e-6);
+
+        // the green one, the amp and the other colors stay
+        press(&mut controls, VirtualKeyCode::Tab);
+        press(&mut controls, VirtualKeyCode::Tab);
+        press(&mut controls, VirtualKeyCode::Left);
+
+        let color = controls.background_color();
+        assert!((controls.amp - 2.0 * STEP).abs() < 1e-6);
+        assert!((color.g - (0.5 - STEP)).abs() < 1e-6);
+        assert_eq!((color.r, color.b), (1.0, 0.0));
+    }
+
+    #[test]
+    fn the_focus_cycles_through_the_sliders() {
+        let mut controls = Controls::new();
+
+        // Shift+Tab first focuses the blue one, then Tab wraps around to the amp one
+        let _ = controls.update(Message::FocusMoved(true));
+        press(&mut controls, VirtualKeyCode::Down);
+        press(&mut controls, VirtualKeyCode::Tab);
+        press(&mut controls, VirtualKeyCode::Up);
+
+        assert!((controls.amp - STEP).abs() < 1e-6);
+        // clamped
+        assert_eq!(controls.background_color().b, 0.0);
+    }
+
+    #[test]
+    fn the_arrow_keys_do_nothing_without_a_focus() {
+        let mut controls = Controls::new();
+        press(&mut controls, VirtualKeyCode::Right);
+
+        assert_eq!(controls.amp, 0.0);
+        assert_eq!(controls.background_color(), Color::from_rgb(1.0, 0.5, 0.0));
+        assert!(Controls::key_message(VirtualKeyCode::A).is_none());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn round_trips_the_state_through_json() {
+        let state = ControlsState {
+            amp: 0.1,
+            background_color: Color::from_rgba(1.0 / 3.0, 0.5, f32::MIN_POSITIVE, 0.7),
+        };
+
+        let json = state.to_json().expect("the state is serializable");
+
+        assert_eq!(ControlsState::from_json(&json).ok(), Some(state));
+    }
+}
@@ -0,0 +1,22 @@
+use iced_winit::Point;
+
+/// A request of the hosted program to change the mouse cursor, e.g. while it drags a knob.
+///
+/// Map the messages of the program to them with [`ChildWindow::set_cursor_requests`].
+///
+/// [`ChildWindow::set_cursor_requests`]: struct.ChildWindow.html#method.set_cursor_requests
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CursorRequest {
+    /// Shows or hides the cursor, see [`ChildWindow::set_cursor_visible`].
+    ///
+    /// [`ChildWindow::set_cursor_visible`]: struct.ChildWindow.html#method.set_cursor_visible
+    SetVisible(bool),
+    /// Grabs or releases the cursor, see [`ChildWindow::set_cursor_grab`].
+    ///
+    /// [`ChildWindow::set_cursor_grab`]: struct.ChildWindow.html#method.set_cursor_grab
+    SetGrab(bool),
+    /// Moves the cursor, see [`ChildWindow::set_cursor_position`].
+    ///
+    /// [`ChildWindow::set_cursor_position`]: struct.ChildWindow.html#method.set_cursor_position
+    SetPosition(Point),
+}
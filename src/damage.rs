@@ -0,0 +1,474 @@
+use std::mem;
+
+use iced_wgpu::{wgpu, Primitive};
+use iced_winit::{Rectangle, Size, Vector};
+
+use crate::background::Background;
+
+/// What a frame with damage tracking redraws, see [`ChildWindowBuilder::damage_tracking`].
+///
+/// [`ChildWindowBuilder::damage_tracking`]: struct.ChildWindowBuilder.html#method.damage_tracking
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Redraw {
+    /// The whole frame.
+    Whole,
+    /// Only the physical region that has changed.
+    Region(Rectangle<u32>),
+    /// Nothing, the last frame is presented again.
+    Nothing,
+}
+
+/// Keeps the frames in a texture of its own, so only the regions of the primitive tree that
+/// have changed since the last frame have to be redrawn into it, and presents it into the frames
+/// of the swap chain, which don't keep their content.
+pub(crate) struct DamageTracker {
+    // drawn into the frame like the texture of a host
+    present: Background,
+    format: wgpu::TextureFormat,
+    target: Option<(wgpu::Texture, wgpu::TextureView, Size<u32>)>,
+    leaves: Vec<Leaf>,
+    clear_color: wgpu::Color,
+    invalidated: bool,
+}
+
+impl DamageTracker {
+    /// Creates the tracker of frames of the `format`.
+    pub(crate) fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        DamageTracker {
+            present: Background::new(device, format),
+            format,
+            target: None,
+            leaves: Vec::new(),
+            clear_color: wgpu::Color::TRANSPARENT,
+            invalidated: true,
+        }
+    }
+
+    /// Redraws the next frame whole, e.g. after something outside of the primitive tree has
+    /// changed.
+    pub(crate) fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+
+    /// Compares the `primitive` tree with the one of the last frame and returns what has to be
+    /// redrawn into [`target`] for it, in physical pixels of the `scale_factor`.
+    ///
+    /// It's [`Redraw::Whole`] for the first frame, after a resize, a change of the
+    /// `clear_color` or an invalidation.
+    ///
+    /// [`target`]: #method.target
+    /// [`Redraw::Whole`]: enum.Redraw.html#variant.Whole
+    pub(crate) fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        size: Size<u32>,
+        primitive: &Primitive,
+        scale_factor: f64,
+        clear_color: wgpu::Color,
+    ) -> Redraw {
+        let mut leaves = Vec::with_capacity(self.leaves.len());
+        collect(primitive, Vector::new(0.0, 0.0), None, &mut leaves);
+        let leaves = mem::replace(&mut self.leaves, leaves);
+
+        if self.target.as_ref().map(|(_, _, target_size)| *target_size) != Some(size) {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth: 1,
+                },
+                array_layer_count: 1,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.format,
+                usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT
+                    | wgpu::TextureUsage::SAMPLED
+                    | wgpu::TextureUsage::COPY_DST,
+            });
+            let view = texture.create_default_view();
+            self.present.set_texture(device, &view, size);
+
+            self.target = Some((texture, view, size));
+            self.invalidated = true;
+        }
+
+        if self.clear_color != clear_color {
+            self.clear_color = clear_color;
+            self.invalidated = true;
+        }
+
+        if mem::replace(&mut self.invalidated, false) {
+            return Redraw::Whole;
+        }
+
+        match damage(&leaves, &self.leaves) {
+            Some(damage) => physical_region(damage, scale_factor, size),
+            None => Redraw::Nothing,
+        }
+    }
+
+    /// The texture the frames are drawn into, once [`prepare`] has created it.
+    ///
+    /// [`prepare`]: #method.prepare
+    pub(crate) fn target(&self) -> Option<&wgpu::TextureView> {
+        self.target.as_ref().map(|(_, view, _)| view)
+    }
+
+    /// Clears the physical `region` of the target with the clear color. A render pass can only
+    /// clear its whole attachment, so a texture of the size of the region is cleared and copied
+    /// into it.
+    pub(crate) fn clear(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        region: Rectangle<u32>,
+    ) {
+        let target = match &self.target {
+            Some((texture, _, _)) => texture,
+            None => return,
+        };
+
+        let extent = wgpu::Extent3d {
+            width: region.width,
+            height: region.height,
+            depth: 1,
+        };
+        let cleared = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: extent,
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+        });
+        let view = cleared.create_default_view();
+
+        let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: &view,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Clear,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: self.clear_color,
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        encoder.copy_texture_to_texture(
+            wgpu::TextureCopyView {
+                texture: &cleared,
+                mip_level: 0,
+                array_layer: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::TextureCopyView {
+                texture: target,
+                mip_level: 0,
+                array_layer: 0,
+                origin: wgpu::Origin3d {
+                    x: region.x,
+                    y: region.y,
+                    z: 0,
+                },
+            },
+            extent,
+        );
+    }
+
+    /// Draws the target into the `frame` of the swap chain.
+    pub(crate) fn present(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        frame: &wgpu::TextureView,
+        size: Size<u32>,
+    ) {
+        self.present.draw(device, encoder, frame, size, None);
+    }
+}
+
+/// A drawn primitive, its logical bounds and what tells it apart from another one.
+#[derive(Debug, Clone, PartialEq)]
+struct Leaf {
+    bounds: Rectangle,
+    // iced's primitives can't be compared, but they're all debug formatted
+    key: String,
+}
+
+/// Collects the leaves of the `primitive` tree, translated by `translation` and clipped to
+/// `clip`.
+fn collect(
+    primitive: &Primitive,
+    translation: Vector,
+    clip: Option<Rectangle>,
+    leaves: &mut Vec<Leaf>,
+) {
+    let bounds = match primitive {
+        Primitive::None => return,
+        Primitive::Group { primitives } => {
+            for primitive in primitives {
+                collect(primitive, translation, clip, leaves);
+            }
+
+            return;
+        }
+        Primitive::Clip {
+            bounds,
+            offset,
+            content,
+        } => {
+            let bounds = translate(*bounds, translation);
+            let clip = Some(clip.map_or(bounds, |clip| intersection(clip, bounds)));
+            let translation = Vector::new(
+                translation.x - offset.x as f32,
+                translation.y - offset.y as f32,
+            );
+
+            collect(content, translation, clip, leaves);
+            return;
+        }
+        Primitive::Translate {
+            translation: offset,
+            content,
+        } => {
+            collect(content, translation + *offset, clip, leaves);
+            return;
+        }
+        Primitive::Cached { cache } => {
+            collect(cache, translation, clip, leaves);
+            return;
+        }
+        // the bounds of a text are anchored by its alignment, they're extended to all of them
+        Primitive::Text { bounds, .. } => Rectangle {
+            x: bounds.x - bounds.width,
+            y: bounds.y - bounds.height,
+            width: bounds.width * 3.0,
+            height: bounds.height * 3.0,
+        },
+        Primitive::Quad { bounds, .. }
+        | Primitive::Image { bounds, .. }
+        | Primitive::Svg { bounds, .. } => *bounds,
+        Primitive::Mesh2D { size, .. } => Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: size.width,
+            height: size.height,
+        },
+    };
+
+    let bounds = translate(bounds, translation);
+
+    leaves.push(Leaf {
+        bounds: clip.map_or(bounds, |clip| intersection(clip, bounds)),
+        key: format!("{:?} {:?} {:?}", translation, clip, primitive),
+    });
+}
+
+/// The logical bounds around the leaves that differ between two frames, `None` if they're the
+/// same.
+fn damage(old: &[Leaf], new: &[Leaf]) -> Option<Rectangle> {
+    let mut damage = None;
+
+    for index in 0..old.len().max(new.len()) {
+        let (old, new) = (old.get(index), new.get(index));
+
+        if old == new {
+            continue;
+        }
+
+        for leaf in old.into_iter().chain(new) {
+            damage = Some(damage.map_or(leaf.bounds, |damage| union(damage, leaf.bounds)));
+        }
+    }
+
+    damage
+}
+
+/// The physical pixels covering the logical `damage` in a frame of the `size`, extended by a
+/// pixel for the anti-aliasing of the edges. The whole frame if they cover it.
+fn physical_region(damage: Rectangle, scale_factor: f64, size: Size<u32>) -> Redraw {
+    let scale_factor = scale_factor as f32;
+    let clamp = |value: f32, max: u32| value.max(0.0).min(max as f32) as u32;
+
+    let x = clamp((damage.x * scale_factor).floor() - 1.0, size.width);
+    let y = clamp((damage.y * scale_factor).floor() - 1.0, size.height);
+    let right = clamp(
+        ((damage.x + damage.width) * scale_factor).ceil() + 1.0,
+        size.width,
+    );
+    let bottom = clamp(
+        ((damage.y + damage.height) * scale_factor).ceil() + 1.0,
+        size.height,
+    );
+
+    if right <= x || bottom <= y {
+        Redraw::Nothing
+    } else if x == 0 && y == 0 && right == size.width && bottom == size.height {
+        Redraw::Whole
+    } else {
+        Redraw::Region(Rectangle {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        })
+    }
+}
+
+fn translate(bounds: Rectangle, translation: Vector) -> Rectangle {
+    Rectangle {
+        x: bounds.x + translation.x,
+        y: bounds.y + translation.y,
+        ..bounds
+    }
+}
+
+/// The part of `b` inside of `a`, empty at its corner if there is none.
+fn intersection(a: Rectangle, b: Rectangle) -> Rectangle {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+
+    Rectangle {
+        x,
+        y,
+        width: ((a.x + a.width).min(b.x + b.width) - x).max(0.0),
+        height: ((a.y + a.height).min(b.y + b.height) - y).max(0.0),
+    }
+}
+
+fn union(a: Rectangle, b: Rectangle) -> Rectangle {
+    // the empty leaves, e.g. clipped away, don't extend it
+    if b.width == 0.0 || b.height == 0.0 {
+        return a;
+    }
+
+    if a.width == 0.0 || a.height == 0.0 {
+        return b;
+    }
+
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+
+    Rectangle {
+        x,
+        y,
+        width: (a.x + a.width).max(b.x + b.width) - x,
+        height: (a.y + a.height).max(b.y + b.height) - y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced_native::image;
+
+    fn image(x: f32, y: f32) -> Primitive {
+        Primitive::Image {
+            handle: image::Handle::from_path("image.png"),
+            bounds: Rectangle {
+                x,
+                y,
+                width: 10.0,
+                height: 10.0,
+            },
+        }
+    }
+
+    fn leaves(primitive: &Primitive) -> Vec<Leaf> {
+        let mut leaves = Vec::new();
+        collect(primitive, Vector::new(0.0, 0.0), None, &mut leaves);
+        leaves
+    }
+
+    #[test]
+    fn an_unchanged_tree_has_no_damage() {
+        let tree = || Primitive::Group {
+            primitives: vec![image(0.0, 0.0), image(20.0, 0.0)],
+        };
+
+        assert_eq!(damage(&leaves(&tree()), &leaves(&tree())), None);
+    }
+
+    #[test]
+    fn a_moved_primitive_damages_both_positions() {
+        let old = leaves(&image(10.0, 10.0));
+        let new = leaves(&image(30.0, 10.0));
+
+        assert_eq!(
+            damage(&old, &new),
+            Some(Rectangle {
+                x: 10.0,
+                y: 10.0,
+                width: 30.0,
+                height: 10.0,
+            })
+        );
+    }
+
+    #[test]
+    fn clips_and_translations_apply_to_the_bounds() {
+        let primitive = Primitive::Translate {
+            translation: Vector::new(5.0, 5.0),
+            content: Box::new(Primitive::Clip {
+                bounds: Rectangle {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 8.0,
+                    height: 8.0,
+                },
+                offset: Vector::new(0, 2),
+                content: Box::new(image(0.0, 0.0)),
+            }),
+        };
+
+        let bounds: Vec<_> = leaves(&primitive)
+            .into_iter()
+            .map(|leaf| leaf.bounds)
+            .collect();
+
+        assert_eq!(
+            bounds,
+            vec![Rectangle {
+                x: 5.0,
+                y: 5.0,
+                width: 8.0,
+                height: 8.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn the_region_is_scaled_and_extended_by_a_pixel() {
+        let damage = Rectangle {
+            x: 10.0,
+            y: 10.0,
+            width: 5.0,
+            height: 5.0,
+        };
+
+        assert_eq!(
+            physical_region(damage, 2.0, Size::new(100, 100)),
+            Redraw::Region(Rectangle {
+                x: 19,
+                y: 19,
+                width: 12,
+                height: 12,
+            })
+        );
+
+        let everything = Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 50.0,
+            height: 50.0,
+        };
+        assert_eq!(
+            physical_region(everything, 2.0, Size::new(100, 100)),
+            Redraw::Whole
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Wakes the main thread on every refresh of the display.
+
+#[cfg(target_os = "macos")]
+pub(crate) use self::macos::DisplayLink;
+
+/// A stub for the platforms without a display link, it's never created.
+#[cfg(not(target_os = "macos"))]
+pub(crate) struct DisplayLink;
+
+#[cfg(not(target_os = "macos"))]
+impl DisplayLink {
+    pub(crate) fn new() -> Option<Self> {
+        None
+    }
+
+    pub(crate) fn take_tick(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::c_void;
+    use std::ptr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use crate::cocoa_util;
+
+    type CVDisplayLinkRef = *mut c_void;
+    type CVReturn = i32;
+
+    const K_CV_RETURN_SUCCESS: CVReturn = 0;
+
+    type CVDisplayLinkOutputCallback = extern "C" fn(
+        display_link: CVDisplayLinkRef,
+        in_now: *const c_void,
+        in_output_time: *const c_void,
+        flags_in: u64,
+        flags_out: *mut u64,
+        context: *mut c_void,
+    ) -> CVReturn;
+
+    #[link(name = "CoreVideo", kind = "framework")]
+    extern "C" {
+        fn CVDisplayLinkCreateWithActiveCGDisplays(
+            display_link_out: *mut CVDisplayLinkRef,
+        ) -> CVReturn;
+        fn CVDisplayLinkSetOutputCallback(
+            display_link: CVDisplayLinkRef,
+            callback: CVDisplayLinkOutputCallback,
+            user_info: *mut c_void,
+        ) -> CVReturn;
+        fn CVDisplayLinkStart(display_link: CVDisplayLinkRef) -> CVReturn;
+        fn CVDisplayLinkStop(display_link: CVDisplayLinkRef) -> CVReturn;
+        fn CVDisplayLinkRelease(display_link: CVDisplayLinkRef);
+    }
+
+    /// A running `CVDisplayLink`.
+    ///
+    /// Its callback runs on a CoreVideo thread, so it only raises a flag and wakes the main run
+    /// loop. winit then runs an iteration of the event loop, where the flag is taken on the main
+    /// thread.
+    pub(crate) struct DisplayLink {
+        link: CVDisplayLinkRef,
+        // boxed, so its address, which the callback gets, stays the same
+        ticked: Box<AtomicBool>,
+    }
+
+    impl DisplayLink {
+        /// Creates and starts a display link for the active displays.
+        pub(crate) fn new() -> Option<Self> {
+            let mut link = ptr::null_mut();
+
+            unsafe {
+                if CVDisplayLinkCreateWithActiveCGDisplays(&mut link) != K_CV_RETURN_SUCCESS {
+                    tracing::warn!("Failed to create a display link");
+                    return None;
+                }
+
+                let display_link = DisplayLink {
+                    link,
+                    ticked: Box::new(AtomicBool::new(false)),
+                };
+
+                let context = &*display_link.ticked as *const AtomicBool as *mut c_void;
+
+                if CVDisplayLinkSetOutputCallback(link, on_refresh, context) != K_CV_RETURN_SUCCESS
+                    || CVDisplayLinkStart(link) != K_CV_RETURN_SUCCESS
+                {
+                    tracing::warn!("Failed to start the display link");
+                    return None;
+                }
+
+                Some(display_link)
+            }
+        }
+
+        /// Whether the display has refreshed since the last call.
+        pub(crate) fn take_tick(&self) -> bool {
+            self.ticked.swap(false, Ordering::AcqRel)
+        }
+    }
+
+    impl Drop for DisplayLink {
+        fn drop(&mut self) {
+            // the callback doesn't run anymore once it's stopped, so the flag can go
+            unsafe {
+                CVDisplayLinkStop(self.link);
+                CVDisplayLinkRelease(self.link);
+            }
+        }
+    }
+
+    extern "C" fn on_refresh(
+        _display_link: CVDisplayLinkRef,
+        _in_now: *const c_void,
+        _in_output_time: *const c_void,
+        _flags_in: u64,
+        _flags_out: *mut u64,
+        context: *mut c_void,
+    ) -> CVReturn {
+        let ticked = unsafe { &*(context as *const AtomicBool) };
+
+        // wake the loop only once per frame we haven't rendered yet
+        if !ticked.swap(true, Ordering::AcqRel) {
+            cocoa_util::wake_main_run_loop();
+        }
+
+        K_CV_RETURN_SUCCESS
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+use std::io;
+
+use iced_wgpu::wgpu;
+use iced_winit::winit::error::OsError;
+
+/// An error that occurred while initializing a [`ChildWindow`].
+///
+/// Surface creation and device request can't fail with the wgpu version we use, so they aren't
+/// represented here.
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+#[derive(Debug)]
+pub enum InitError {
+    /// winit failed to create the child window.
+    Window(OsError),
+    /// The child window couldn't be embedded into the parent.
+    Embed(EmbedError),
+    /// No adapter is compatible with the requested options and the window surface.
+    NoAdapter {
+        power_preference: wgpu::PowerPreference,
+        backends: wgpu::BackendBit,
+    },
+    /// The thread pool running the commands of the program couldn't be started.
+    Executor(io::Error),
+    /// The swap chains of the backend of a device supplied by the host can't have the format
+    /// the options call for.
+    UnsupportedFormat {
+        format: wgpu::TextureFormat,
+        backend: wgpu::Backend,
+    },
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InitError::Window(error) => write!(f, "failed to create window: {}", error),
+            InitError::Embed(error) => write!(f, "failed to embed window: {}", error),
+            InitError::NoAdapter {
+                power_preference,
+                backends,
+            } => write!(
+                f,
+                "no adapter found for {:?} power preference and {:?} backends",
+                power_preference, backends
+            ),
+            InitError::Executor(error) => write!(f, "failed to start executor: {}", error),
+            InitError::UnsupportedFormat { format, backend } => write!(
+                f,
+                "{:?} swap chain format not supported by {:?} backend",
+                format, backend
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InitError::Window(error) => Some(error),
+            InitError::Embed(error) => Some(error),
+            InitError::NoAdapter { .. } => None,
+            InitError::Executor(error) => Some(error),
+            InitError::UnsupportedFormat { .. } => None,
+        }
+    }
+}
+
+impl From<OsError> for InitError {
+    fn from(error: OsError) -> Self {
+        InitError::Window(error)
+    }
+}
+
+impl From<EmbedError> for InitError {
+    fn from(error: EmbedError) -> Self {
+        InitError::Embed(error)
+    }
+}
+
+/// An error that occurred while embedding a child window into a parent.
+#[derive(Debug)]
+pub enum EmbedError {
+    /// The parent handle belongs to a platform we can't embed into.
+    UnsupportedParent,
+    /// The platform call that reparents the window failed.
+    Os(io::Error),
+}
+
+impl fmt::Display for EmbedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmbedError::UnsupportedParent => write!(f, "unsupported parent window handle"),
+            EmbedError::Os(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for EmbedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EmbedError::UnsupportedParent => None,
+            EmbedError::Os(error) => Some(error),
+        }
+    }
+}
+
+/// An error that occurred while capturing a frame to a file.
+#[derive(Debug)]
+pub enum CaptureError {
+    /// The buffer with the rendered pixels couldn't be mapped.
+    Map(wgpu::BufferAsyncErr),
+    /// The file couldn't be created or written.
+    Io(io::Error),
+    /// The pixels couldn't be encoded.
+    Encode(png::EncodingError),
+    /// A reference image couldn't be decoded.
+    Decode(png::DecodingError),
+    /// The frames are rendered in a format that can't be written as an 8-bit PNG, e.g. an HDR
+    /// one.
+    UnsupportedFormat(wgpu::TextureFormat),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaptureError::Map(_) => write!(f, "failed to map the frame buffer"),
+            CaptureError::Io(error) => write!(f, "failed to write the frame: {}", error),
+            CaptureError::Encode(error) => write!(f, "failed to encode the frame: {}", error),
+            CaptureError::Decode(error) => write!(f, "failed to decode the image: {}", error),
+            CaptureError::UnsupportedFormat(format) => {
+                write!(f, "can't capture frames in {:?} format", format)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CaptureError::Map(_) => None,
+            CaptureError::Io(error) => Some(error),
+            CaptureError::Encode(error) => Some(error),
+            CaptureError::Decode(error) => Some(error),
+            CaptureError::UnsupportedFormat(_) => None,
+        }
+    }
+}
+
+impl From<wgpu::BufferAsyncErr> for CaptureError {
+    fn from(error: wgpu::BufferAsyncErr) -> Self {
+        CaptureError::Map(error)
+    }
+}
+
+impl From<io::Error> for CaptureError {
+    fn from(error: io::Error) -> Self {
+        CaptureError::Io(error)
+    }
+}
+
+impl From<png::EncodingError> for CaptureError {
+    fn from(error: png::EncodingError) -> Self {
+        CaptureError::Encode(error)
+    }
+}
+
+impl From<png::DecodingError> for CaptureError {
+    fn from(error: png::DecodingError) -> Self {
+        CaptureError::Decode(error)
+    }
+}
@@ -0,0 +1,175 @@
+//! Receives the files dragged onto the view.
+
+use std::path::PathBuf;
+
+#[cfg(target_os = "macos")]
+pub(crate) use self::macos::DropTarget;
+
+/// A file dragged onto the window, see [`ChildWindow::on_file_drop`].
+///
+/// A file dragged together with others is reported on its own, each path in its own
+/// `Hovered` or `Dropped`.
+///
+/// [`ChildWindow::on_file_drop`]: struct.ChildWindow.html#method.on_file_drop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileDrop {
+    /// The file is dragged over the window.
+    Hovered(PathBuf),
+    /// The file has been dropped onto the window.
+    Dropped(PathBuf),
+    /// The files have been dragged out of the window again, or the drag has been cancelled.
+    Cancelled,
+}
+
+/// A stub for the platforms where winit receives the drops itself, it's never created.
+#[cfg(not(target_os = "macos"))]
+pub(crate) struct DropTarget;
+
+#[cfg(not(target_os = "macos"))]
+impl DropTarget {
+    pub(crate) fn take_drops(&self) -> Vec<FileDrop> {
+        Vec::new()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::Once;
+
+    use cocoa::{
+        base::{id, BOOL, YES},
+        foundation::NSUInteger,
+    };
+    use objc::runtime::{Class, Object, Sel};
+
+    use super::FileDrop;
+    use crate::cocoa_util;
+
+    /// The `NSDragOperationCopy` of `NSDragOperation`.
+    const NS_DRAG_OPERATION_COPY: NSUInteger = 1;
+
+    thread_local! {
+        // the drags of each view since they were taken last, by its address. A view whose class
+        // is swapped can't get an instance variable, it's allocated with the size of winit's
+        // class already.
+        static DROPS: RefCell<HashMap<usize, Vec<FileDrop>>> = RefCell::new(HashMap::new());
+    }
+
+    /// Makes a view a dragging destination of files.
+    ///
+    /// winit receives the drops in the delegate of the window it has created, which doesn't
+    /// host the view anymore once it's attached to a parent. So the class of the view is swapped
+    /// for a subclass that receives them itself, and swapped back when the target is dropped.
+    pub(crate) struct DropTarget {
+        view: id,
+        class: &'static Class,
+    }
+
+    impl DropTarget {
+        /// Registers the `view` for the dragged files.
+        ///
+        /// # Safety
+        ///
+        /// `view` must be a valid `NSView` of winit, which outlives the target, and it must be
+        /// called on the main thread.
+        pub(crate) unsafe fn new(view: id) -> Self {
+            DROPS.with(|drops| drops.borrow_mut().insert(view as usize, Vec::new()));
+
+            let class = cocoa_util::object_class(view);
+            cocoa_util::swap_view_class(view, drop_target_class(class));
+            cocoa_util::register_file_drops(view);
+
+            DropTarget { view, class }
+        }
+
+        /// The drags since the last call.
+        pub(crate) fn take_drops(&self) -> Vec<FileDrop> {
+            DROPS.with(|drops| {
+                drops
+                    .borrow_mut()
+                    .get_mut(&(self.view as usize))
+                    .map(|drops| drops.split_off(0))
+                    .unwrap_or_default()
+            })
+        }
+    }
+
+    impl Drop for DropTarget {
+        fn drop(&mut self) {
+            // the view doesn't push drops anymore once it has its class back
+            unsafe {
+                cocoa_util::unregister_file_drops(self.view);
+                cocoa_util::swap_view_class(self.view, self.class);
+            }
+
+            DROPS.with(|drops| drops.borrow_mut().remove(&(self.view as usize)));
+        }
+    }
+
+    /// The subclass of the class of winit's views, declared on the first call.
+    ///
+    /// Each plugin binary linking the crate into the same host declares its own, with the
+    /// address of its `DECLARE` in the name, because the methods push into the drops of that
+    /// binary.
+    fn drop_target_class(superclass: &'static Class) -> &'static Class {
+        static DECLARE: Once = Once::new();
+        static mut CLASS: Option<&'static Class> = None;
+
+        DECLARE.call_once(|| unsafe {
+            let name = format!(
+                "IcedChildWinDropTarget{:x}",
+                &DECLARE as *const Once as usize
+            );
+            CLASS = Some(
+                cocoa_util::declare_drop_target_class(
+                    &name,
+                    superclass,
+                    dragging_entered,
+                    dragging_exited,
+                    perform_drag_operation,
+                )
+                .expect("the drop target class is declared only once per binary"),
+            );
+        });
+
+        unsafe { CLASS.expect("the drop target class is declared") }
+    }
+
+    extern "C" fn dragging_entered(this: &Object, _: Sel, sender: id) -> NSUInteger {
+        unsafe { push_paths(this, sender, FileDrop::Hovered) };
+
+        NS_DRAG_OPERATION_COPY
+    }
+
+    extern "C" fn dragging_exited(this: &Object, _: Sel, _sender: id) {
+        push(this, FileDrop::Cancelled);
+    }
+
+    extern "C" fn perform_drag_operation(this: &Object, _: Sel, sender: id) -> BOOL {
+        unsafe { push_paths(this, sender, FileDrop::Dropped) };
+
+        YES
+    }
+
+    unsafe fn push_paths(this: &Object, sender: id, drop: fn(PathBuf) -> FileDrop) {
+        for path in cocoa_util::dragged_filenames(sender) {
+            push(this, drop(path));
+        }
+    }
+
+    fn push(this: &Object, drop: FileDrop) {
+        let view = this as *const Object as usize;
+
+        DROPS.with(|drops| {
+            if let Some(drops) = drops.borrow_mut().get_mut(&view) {
+                drops.push(drop);
+            }
+        });
+
+        // the drag runs in the run loop of AppKit, winit has to run an iteration to deliver it
+        cocoa_util::wake_main_run_loop();
+    }
+}
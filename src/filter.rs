@@ -0,0 +1,10 @@
+/// What an event filter set with [`ChildWindow::set_event_filter`] does with an event.
+///
+/// [`ChildWindow::set_event_filter`]: struct.ChildWindow.html#method.set_event_filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    /// The event is queued to iced.
+    Pass,
+    /// The event isn't queued to iced, e.g. a shortcut the host has handled.
+    Consume,
+}
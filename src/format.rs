@@ -0,0 +1,183 @@
+use iced_wgpu::wgpu;
+
+/// The formats we present in, by preference. iced expects an sRGB framebuffer to blend
+/// correctly, so the linear formats are the last resort.
+const PREFERRED_FORMATS: [wgpu::TextureFormat; 4] = [
+    wgpu::TextureFormat::Bgra8UnormSrgb,
+    wgpu::TextureFormat::Rgba8UnormSrgb,
+    wgpu::TextureFormat::Bgra8Unorm,
+    wgpu::TextureFormat::Rgba8Unorm,
+];
+
+/// The formats we present in with HDR output, by preference. The float one can exceed 1, the
+/// 10-bit one only has more precision. iced writes linear colors into both.
+const HDR_FORMATS: [wgpu::TextureFormat; 2] = [
+    wgpu::TextureFormat::Rgba16Float,
+    wgpu::TextureFormat::Rgb10a2Unorm,
+];
+
+/// Selects the swap chain format for the surfaces of the `backend`, an HDR one if `hdr` and the
+/// backend has one, an SDR one otherwise. `force_srgb` restricts the SDR formats to the sRGB or
+/// the linear ones, and excludes the HDR ones if it's `true`.
+///
+/// wgpu doesn't let us query the formats a surface supports yet, so we rely on the formats the
+/// swap chains of the backend can be created with.
+pub(crate) fn select(
+    backend: wgpu::Backend,
+    hdr: bool,
+    force_srgb: Option<bool>,
+) -> wgpu::TextureFormat {
+    let supported = supported_formats(backend);
+
+    if hdr && force_srgb != Some(true) {
+        let format = HDR_FORMATS
+            .iter()
+            .copied()
+            .find(|format| supported.contains(format));
+
+        match format {
+            Some(format) => {
+                tracing::debug!(
+                    "Selected {:?} HDR swap chain format for {:?}",
+                    format,
+                    backend
+                );
+
+                return format;
+            }
+            None => tracing::warn!(
+                "{:?} has no HDR swap chain format, falling back to SDR",
+                backend
+            ),
+        }
+    }
+
+    let format = PREFERRED_FORMATS
+        .iter()
+        .copied()
+        .filter(|&format| force_srgb.map_or(true, |srgb| is_srgb(format) == srgb))
+        .find(|format| supported.contains(format))
+        .unwrap_or(if force_srgb == Some(false) {
+            wgpu::TextureFormat::Bgra8Unorm
+        } else {
+            wgpu::TextureFormat::Bgra8UnormSrgb
+        });
+
+    tracing::debug!("Selected {:?} swap chain format for {:?}", format, backend);
+
+    format
+}
+
+/// Whether wgpu encodes the linear colors written into the `format` to sRGB.
+pub(crate) fn is_srgb(format: wgpu::TextureFormat) -> bool {
+    match format {
+        wgpu::TextureFormat::Bgra8UnormSrgb | wgpu::TextureFormat::Rgba8UnormSrgb => true,
+        _ => false,
+    }
+}
+
+/// Whether the swap chains of the `backend` can be created with the `format`.
+pub(crate) fn is_supported(backend: wgpu::Backend, format: wgpu::TextureFormat) -> bool {
+    supported_formats(backend).contains(&format)
+}
+
+/// Whether the `format` is one of the HDR formats.
+pub(crate) fn is_hdr(format: wgpu::TextureFormat) -> bool {
+    HDR_FORMATS.contains(&format)
+}
+
+fn supported_formats(backend: wgpu::Backend) -> &'static [wgpu::TextureFormat] {
+    match backend {
+        // CAMetalLayer
+        wgpu::Backend::Metal => &[
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::TextureFormat::Rgba16Float,
+            wgpu::TextureFormat::Rgb10a2Unorm,
+        ],
+        // DXGI flip model swap chains can't be sRGB, gfx presents the sRGB formats with a linear
+        // swap chain and an sRGB render target view
+        wgpu::Backend::Dx12 | wgpu::Backend::Dx11 => &[
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Rgba16Float,
+            wgpu::TextureFormat::Rgb10a2Unorm,
+        ],
+        // it depends on the driver, but these are the common ones
+        _ => &[
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::TextureFormat::Rgba8Unorm,
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_srgb_on_every_backend() {
+        for &backend in &[
+            wgpu::Backend::Metal,
+            wgpu::Backend::Dx12,
+            wgpu::Backend::Dx11,
+            wgpu::Backend::Vulkan,
+        ] {
+            assert_eq!(
+                select(backend, false, None),
+                wgpu::TextureFormat::Bgra8UnormSrgb
+            );
+        }
+    }
+
+    #[test]
+    fn forces_a_linear_format() {
+        assert_eq!(
+            select(wgpu::Backend::Dx12, false, Some(false)),
+            wgpu::TextureFormat::Bgra8Unorm
+        );
+        assert!(!is_srgb(select(wgpu::Backend::Metal, false, Some(false))));
+    }
+
+    #[test]
+    fn selects_hdr_unless_srgb_is_forced() {
+        assert_eq!(
+            select(wgpu::Backend::Metal, true, None),
+            wgpu::TextureFormat::Rgba16Float
+        );
+        assert_eq!(
+            select(wgpu::Backend::Metal, true, Some(true)),
+            wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        assert_eq!(
+            select(wgpu::Backend::Vulkan, true, None),
+            wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+    }
+
+    #[test]
+    fn validates_the_format_for_the_backend() {
+        assert!(!is_supported(
+            wgpu::Backend::Metal,
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        ));
+        assert!(!is_supported(
+            wgpu::Backend::Vulkan,
+            wgpu::TextureFormat::Rgba16Float
+        ));
+
+        for &backend in &[
+            wgpu::Backend::Metal,
+            wgpu::Backend::Dx12,
+            wgpu::Backend::Vulkan,
+        ] {
+            for &(hdr, force_srgb) in &[(false, None), (false, Some(false)), (true, None)] {
+                assert!(is_supported(backend, select(backend, hdr, force_srgb)));
+            }
+        }
+    }
+}
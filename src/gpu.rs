@@ -0,0 +1,105 @@
+use iced_wgpu::wgpu;
+use iced_winit::futures;
+
+use crate::InitError;
+
+/// The adapter a window renders with, e.g. for bug reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuInfo {
+    /// The name of the GPU.
+    pub name: String,
+    /// The graphics API it's driven with, e.g. Metal or Vulkan.
+    pub backend: wgpu::Backend,
+    /// Whether it's an integrated, discrete, virtual GPU or the CPU.
+    pub device_type: wgpu::DeviceType,
+}
+
+impl From<wgpu::AdapterInfo> for GpuInfo {
+    fn from(info: wgpu::AdapterInfo) -> Self {
+        GpuInfo {
+            name: info.name,
+            backend: info.backend,
+            device_type: info.device_type,
+        }
+    }
+}
+
+/// Requests an adapter, which can present to the `compatible_surface` if there is one, and its
+/// device.
+pub(crate) fn request(
+    power_preference: wgpu::PowerPreference,
+    backends: wgpu::BackendBit,
+    anisotropic_filtering: bool,
+    limits: &wgpu::Limits,
+    compatible_surface: Option<&wgpu::Surface>,
+) -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), InitError> {
+    let span = tracing::info_span!("request_gpu", ?power_preference, ?backends);
+    let _enter = span.enter();
+
+    let adapter = futures::executor::block_on(wgpu::Adapter::request(
+        &wgpu::RequestAdapterOptions {
+            power_preference,
+            compatible_surface,
+        },
+        backends,
+    ))
+    .ok_or(InitError::NoAdapter {
+        power_preference,
+        backends,
+    })?;
+
+    let info = GpuInfo::from(adapter.get_info());
+    tracing::info!(
+        "Using {} ({:?}, {:?})",
+        info.name,
+        info.backend,
+        info.device_type
+    );
+
+    // wgpu doesn't expose the features of the adapter yet, so we can't check the extension, the
+    // samplers just don't filter anisotropically if it's unsupported. The limits are asserted
+    // against the maximum wgpu supports, though.
+    let max_bind_groups = if limits.max_bind_groups > wgpu::MAX_BIND_GROUPS as u32 {
+        tracing::warn!(
+            "{} bind groups requested, but at most {} are supported",
+            limits.max_bind_groups,
+            wgpu::MAX_BIND_GROUPS
+        );
+
+        wgpu::MAX_BIND_GROUPS as u32
+    } else {
+        limits.max_bind_groups
+    };
+
+    let (device, queue) =
+        futures::executor::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            extensions: wgpu::Extensions {
+                anisotropic_filtering,
+            },
+            limits: wgpu::Limits { max_bind_groups },
+        }));
+
+    Ok((adapter, device, queue))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_backend_has_no_adapter() {
+        let result = request(
+            wgpu::PowerPreference::Default,
+            wgpu::BackendBit::empty(),
+            false,
+            &wgpu::Limits::default(),
+            None,
+        );
+
+        match result {
+            Err(InitError::NoAdapter { backends, .. }) => assert!(backends.is_empty()),
+            Err(error) => panic!("unexpected error: {}", error),
+            Ok(_) => panic!("an adapter was found without a backend"),
+        }
+    }
+}
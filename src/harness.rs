@@ -0,0 +1,215 @@
+use std::path::Path;
+#[cfg(feature = "json")]
+use std::{io, thread, time::Instant};
+
+#[cfg(feature = "testing")]
+use iced_wgpu::Primitive;
+use iced_wgpu::{wgpu, Renderer};
+use iced_winit::{winit::event::WindowEvent, Program, Size};
+
+use crate::capture;
+use crate::input::Input;
+#[cfg(feature = "json")]
+use crate::recording::{self, TimedEvent};
+use crate::{InitError, OffscreenRenderer};
+
+/// Drives a program with scripted winit events, frame by frame, without a window or an event
+/// loop.
+///
+/// The events are converted like the ones of a [`ChildWindow`], the cursor moves are coalesced
+/// until the next step too, so a test can feed a sequence of them, [`step`] and inspect the
+/// [`program`] after each update.
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+/// [`step`]: #method.step
+/// [`program`]: #method.program
+pub struct TestHarness<P: 'static + Program<Renderer = Renderer>> {
+    renderer: OffscreenRenderer<P>,
+    size: Size<u32>,
+    input: Input,
+}
+
+impl<P: 'static + Program<Renderer = Renderer>> TestHarness<P> {
+    /// Creates a harness laying out the `program` in the physical `size`.
+    pub fn new(program: P, size: Size<u32>) -> Result<Self, InitError> {
+        let mut renderer = OffscreenRenderer::new(program)?;
+        renderer.set_size(size);
+
+        Ok(TestHarness {
+            renderer,
+            size,
+            input: Input::default(),
+        })
+    }
+
+    /// The program driven by the harness.
+    pub fn program(&self) -> &P {
+        self.renderer.program()
+    }
+
+    /// The primitive tree of the last [`step`], see [`PrimitiveSummary`].
+    ///
+    /// [`step`]: #method.step
+    /// [`PrimitiveSummary`]: struct.PrimitiveSummary.html
+    #[cfg(feature = "testing")]
+    pub fn current_primitive(&self) -> &Primitive {
+        self.renderer.current_primitive()
+    }
+
+    /// The renderer of the program, e.g. to change the scale factor or queue messages.
+    pub fn renderer(&mut self) -> &mut OffscreenRenderer<P> {
+        &mut self.renderer
+    }
+
+    /// Queues the `event` to the program. It's handled on the next [`step`].
+    ///
+    /// The positions are physical, like the ones winit reports.
+    ///
+    /// [`step`]: #method.step
+    pub fn feed_event(&mut self, event: WindowEvent<'_>) {
+        if let WindowEvent::Resized(new_size) = &event {
+            self.size = Size::new(new_size.width, new_size.height);
+            self.renderer.set_size(self.size);
+        }
+
+        // there's no parent to scale differently, the points are the logical units
+        let scale_factor = self.renderer.scale_factor();
+
+        if let Some(event) = self.input.convert(&event, scale_factor, 1.0, false) {
+            if let Some(event) = self.input.coalesce(event) {
+                self.flush_cursor_moved();
+                self.renderer.queue_event(event);
+            }
+        }
+    }
+
+    /// Updates the program with the fed events.
+    pub fn step(&mut self) {
+        self.flush_cursor_moved();
+        self.renderer.update();
+    }
+
+    fn flush_cursor_moved(&mut self) {
+        if let Some(event) = self.input.take_cursor_moved() {
+            self.renderer.queue_event(event);
+        }
+    }
+
+    /// Replays the events an [`EventRecorder`] has recorded into the file at `path`, at their
+    /// original cadence, stepping after each of them.
+    ///
+    /// [`EventRecorder`]: struct.EventRecorder.html
+    #[cfg(feature = "json")]
+    pub fn replay(&mut self, path: &Path) -> io::Result<()> {
+        let start = Instant::now();
+
+        for TimedEvent { time, event } in recording::read(path)? {
+            if let Some(delay) = time.checked_sub(start.elapsed()) {
+                thread::sleep(delay);
+            }
+
+            self.feed_event(event.to_window_event());
+            self.step();
+        }
+
+        Ok(())
+    }
+
+    /// Updates the program with the fed events and renders it.
+    ///
+    /// Returns the RGBA pixels of the frame, row by row.
+    pub fn render(&mut self) -> Result<Vec<u8>, wgpu::BufferAsyncErr> {
+        self.flush_cursor_moved();
+        self.renderer.render_to_buffer(self.size)
+    }
+
+    /// Renders a frame and asserts that it matches the `golden` PNG file.
+    ///
+    /// The channels of a pixel may differ by `tolerance`, a fraction of their range, because GPU
+    /// drivers rasterize the edges and blend a bit differently across machines, e.g. 0.02 for
+    /// the anti-aliased text.
+    ///
+    /// # Panics
+    ///
+    /// If the frame doesn't match or can't be compared. Then the frame is written to
+    /// `actual.png` and the mismatching pixels are marked red in `diff.png`, next to the golden
+    /// file, so a changed rendering can be reviewed or copied over the golden file.
+    pub fn assert_frame_matches(&mut self, golden: &Path, tolerance: f32) {
+        let actual = self
+            .render()
+            .expect("failed to read the rendered frame back");
+
+        let directory = golden.parent().unwrap_or_else(|| Path::new("."));
+        let actual_path = directory.join("actual.png");
+        let diff_path = directory.join("diff.png");
+
+        let write = |path: &Path, pixels: &[u8]| {
+            if let Err(error) = capture::write_png(path, self.size, pixels) {
+                panic!("failed to write {}: {}", path.display(), error);
+            }
+        };
+
+        let (size, expected) = match capture::read_png(golden) {
+            Ok(Some(golden)) => golden,
+            Ok(None) => panic!("{} isn't an 8-bit RGBA image", golden.display()),
+            Err(error) => {
+                write(&actual_path, &actual);
+                panic!(
+                    "failed to read {}: {}, the frame is written to {}",
+                    golden.display(),
+                    error,
+                    actual_path.display()
+                );
+            }
+        };
+
+        if size != self.size {
+            write(&actual_path, &actual);
+            panic!(
+                "the frame is {}x{}, but {} is {}x{}, it's written to {}",
+                self.size.width,
+                self.size.height,
+                golden.display(),
+                size.width,
+                size.height,
+                actual_path.display()
+            );
+        }
+
+        let max_difference = (tolerance.max(0.0).min(1.0) * 255.0).round() as i16;
+        let mut mismatches = 0;
+
+        let diff: Vec<u8> = actual
+            .chunks(4)
+            .zip(expected.chunks(4))
+            .flat_map(|(actual, expected)| {
+                let matches = actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(a, b)| (*a as i16 - *b as i16).abs() <= max_difference);
+
+                if matches {
+                    // the matching content is faded, so the mismatches stand out
+                    vec![actual[0], actual[1], actual[2], 64]
+                } else {
+                    mismatches += 1;
+                    vec![255, 0, 0, 255]
+                }
+            })
+            .collect();
+
+        if mismatches > 0 {
+            write(&actual_path, &actual);
+            write(&diff_path, &diff);
+
+            panic!(
+                "{} pixels of the frame differ from {} by more than {}, see {} and {}",
+                mismatches,
+                golden.display(),
+                tolerance,
+                actual_path.display(),
+                diff_path.display()
+            );
+        }
+    }
+}
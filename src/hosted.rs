@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use iced_wgpu::Renderer;
+use iced_winit::{Command, Element, Program};
+
+use crate::CursorRequest;
+
+/// A callback invoked for each message the hosted program handles.
+pub(crate) type OnMessage<M> = Rc<RefCell<Option<Box<dyn FnMut(&M)>>>>;
+
+/// The cursor requests of the messages the hosted program handles, shared with the window.
+pub(crate) type SharedCursorRequests<M> = Rc<RefCell<CursorRequests<M>>>;
+
+/// Collects the cursor requests until the window applies them after the update.
+pub(crate) struct CursorRequests<M> {
+    pub(crate) request: Option<fn(&M) -> Option<CursorRequest>>,
+    pub(crate) pending: Vec<CursorRequest>,
+}
+
+impl<M> Default for CursorRequests<M> {
+    fn default() -> Self {
+        CursorRequests {
+            request: None,
+            pending: Vec::new(),
+        }
+    }
+}
+
+/// Wraps the hosted program to let the host observe the messages it handles.
+pub(crate) struct Hosted<P: Program> {
+    pub(crate) program: P,
+    on_message: OnMessage<P::Message>,
+    cursor_requests: SharedCursorRequests<P::Message>,
+}
+
+impl<P: Program> Hosted<P> {
+    /// Wraps the `program`, which reports its messages to `on_message` and their cursor requests
+    /// to `cursor_requests`.
+    pub(crate) fn new(
+        program: P,
+        on_message: OnMessage<P::Message>,
+        cursor_requests: SharedCursorRequests<P::Message>,
+    ) -> Self {
+        Hosted {
+            program,
+            on_message,
+            cursor_requests,
+        }
+    }
+}
+
+impl<P: Program<Renderer = Renderer>> Program for Hosted<P> {
+    type Renderer = Renderer;
+    type Message = P::Message;
+
+    fn update(&mut self, message: P::Message) -> Command<P::Message> {
+        if let Some(on_message) = self.on_message.borrow_mut().as_mut() {
+            on_message(&message);
+        }
+
+        {
+            let mut cursor_requests = self.cursor_requests.borrow_mut();
+
+            if let Some(request) = cursor_requests
+                .request
+                .and_then(|request| request(&message))
+            {
+                cursor_requests.pending.push(request);
+            }
+        }
+
+        self.program.update(message)
+    }
+
+    fn view(&mut self) -> Element<P::Message, Renderer> {
+        self.program.view()
+    }
+}
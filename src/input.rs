@@ -0,0 +1,336 @@
+use iced_winit::{mouse, winit, Event};
+use winit::event::{ElementState, ModifiersState, MouseButton, MouseScrollDelta, WindowEvent};
+
+/// Converts the winit events of a window into the iced events of its program, for a
+/// [`ChildWindow`] and a [`TestHarness`] alike.
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+/// [`TestHarness`]: struct.TestHarness.html
+#[derive(Debug, Default)]
+pub(crate) struct Input {
+    modifiers: ModifiersState,
+    control_click: bool,
+    pending_cursor_moved: Option<Event>,
+    coalesced_cursor_moves: usize,
+}
+
+impl Input {
+    /// The modifiers held down.
+    pub(crate) fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Converts the `event`, or only keeps track of it if it's `consumed`, e.g. by an event
+    /// filter.
+    ///
+    /// `scale_factor` converts the physical positions winit reports to logical units of the
+    /// program, `units_per_point` converts the pixel deltas of the scrolls.
+    pub(crate) fn convert(
+        &mut self,
+        event: &WindowEvent<'_>,
+        scale_factor: f64,
+        units_per_point: f64,
+        consumed: bool,
+    ) -> Option<Event> {
+        match event {
+            WindowEvent::ModifiersChanged(new_modifiers) => {
+                self.modifiers = *new_modifiers;
+            }
+            // we don't get the key up of a modifier that is released after we lose the focus, e.g.
+            // on Cmd+Tab, and it would stick
+            WindowEvent::Focused(false) => {
+                self.modifiers = ModifiersState::default();
+            }
+            // a Control-click is the secondary click on macOS, context menus open on either
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } if !consumed && cfg!(target_os = "macos") && self.modifiers.ctrl() => {
+                self.control_click = true;
+
+                return Some(Event::Mouse(mouse::Event::ButtonPressed(
+                    mouse::Button::Right,
+                )));
+            }
+            // even if Control is released first
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } if self.control_click => {
+                self.control_click = false;
+
+                return Some(Event::Mouse(mouse::Event::ButtonReleased(
+                    mouse::Button::Right,
+                )));
+            }
+            WindowEvent::MouseWheel { delta, .. } if !consumed => {
+                return Some(Event::Mouse(mouse::Event::WheelScrolled {
+                    delta: scroll_delta(*delta, units_per_point),
+                }));
+            }
+            _ => {}
+        }
+
+        if consumed {
+            return None;
+        }
+
+        iced_winit::conversion::window_event(event, scale_factor, self.modifiers)
+    }
+
+    /// Coalesces the cursor moves until the next update, a fast drag moves the cursor many times
+    /// per frame.
+    ///
+    /// Returns the `event` if it isn't a move. Take the latest move with [`take_cursor_moved`]
+    /// before it, so the buttons are still pressed and released where the cursor was then.
+    ///
+    /// [`take_cursor_moved`]: #method.take_cursor_moved
+    pub(crate) fn coalesce(&mut self, event: Event) -> Option<Event> {
+        if let Event::Mouse(mouse::Event::CursorMoved { .. }) = event {
+            if self.pending_cursor_moved.replace(event).is_some() {
+                self.coalesced_cursor_moves += 1;
+            }
+
+            return None;
+        }
+
+        Some(event)
+    }
+
+    /// Takes the latest cursor move since the last one taken.
+    pub(crate) fn take_cursor_moved(&mut self) -> Option<Event> {
+        self.pending_cursor_moved.take()
+    }
+
+    /// Takes the count of the cursor moves coalesced since the last time it was taken.
+    pub(crate) fn take_coalesced_cursor_moves(&mut self) -> usize {
+        std::mem::replace(&mut self.coalesced_cursor_moves, 0)
+    }
+}
+
+/// Converts a winit scroll delta into the units iced scrolls by.
+///
+/// Mouse wheels report lines, which iced scrolls by itself, trackpads report pixels. winit gives
+/// the pixels in points, they're converted to the logical units of the program by
+/// `units_per_point`.
+fn scroll_delta(delta: MouseScrollDelta, units_per_point: f64) -> mouse::ScrollDelta {
+    match delta {
+        MouseScrollDelta::LineDelta(x, y) => mouse::ScrollDelta::Lines { x, y },
+        MouseScrollDelta::PixelDelta(position) => mouse::ScrollDelta::Pixels {
+            x: (position.x * units_per_point) as f32,
+            y: (position.y * units_per_point) as f32,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced_winit::keyboard;
+    use winit::{
+        dpi::{LogicalPosition, PhysicalPosition},
+        event::{DeviceId, KeyboardInput, TouchPhase, VirtualKeyCode},
+    };
+
+    #[allow(deprecated)]
+    fn mouse_wheel(delta: MouseScrollDelta) -> WindowEvent<'static> {
+        WindowEvent::MouseWheel {
+            device_id: unsafe { DeviceId::dummy() },
+            delta,
+            phase: TouchPhase::Moved,
+            modifiers: ModifiersState::default(),
+        }
+    }
+
+    #[allow(deprecated)]
+    fn cursor_moved(x: f64, y: f64) -> WindowEvent<'static> {
+        WindowEvent::CursorMoved {
+            device_id: unsafe { DeviceId::dummy() },
+            position: PhysicalPosition::new(x, y),
+            modifiers: ModifiersState::default(),
+        }
+    }
+
+    #[allow(deprecated)]
+    fn left_button(state: ElementState) -> WindowEvent<'static> {
+        WindowEvent::MouseInput {
+            device_id: unsafe { DeviceId::dummy() },
+            state,
+            button: MouseButton::Left,
+            modifiers: ModifiersState::default(),
+        }
+    }
+
+    #[allow(deprecated)]
+    fn key_pressed(virtual_keycode: VirtualKeyCode) -> WindowEvent<'static> {
+        WindowEvent::KeyboardInput {
+            device_id: unsafe { DeviceId::dummy() },
+            input: KeyboardInput {
+                scancode: 0,
+                state: ElementState::Pressed,
+                virtual_keycode: Some(virtual_keycode),
+                modifiers: ModifiersState::default(),
+            },
+            is_synthetic: false,
+        }
+    }
+
+    #[test]
+    fn clicks_at_the_logical_position_of_a_physical_pixel() {
+        let mut input = Input::default();
+
+        // a Retina display
+        assert_eq!(
+            input.convert(&cursor_moved(300.0, 150.0), 2.0, 1.0, false),
+            Some(Event::Mouse(mouse::Event::CursorMoved {
+                x: 150.0,
+                y: 75.0
+            }))
+        );
+        assert_eq!(
+            input.convert(&left_button(ElementState::Pressed), 2.0, 1.0, false),
+            Some(Event::Mouse(mouse::Event::ButtonPressed(
+                mouse::Button::Left
+            )))
+        );
+
+        // a scale factor overridden to 1 on it, the logical units are pixels
+        assert_eq!(
+            input.convert(&cursor_moved(300.0, 150.0), 1.0, 2.0, false),
+            Some(Event::Mouse(mouse::Event::CursorMoved {
+                x: 300.0,
+                y: 150.0
+            }))
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn turns_a_control_click_into_a_right_click() {
+        let mut input = Input::default();
+        input.convert(
+            &WindowEvent::ModifiersChanged(ModifiersState::CTRL),
+            1.0,
+            1.0,
+            false,
+        );
+
+        assert_eq!(
+            input.convert(&left_button(ElementState::Pressed), 1.0, 1.0, false),
+            Some(Event::Mouse(mouse::Event::ButtonPressed(
+                mouse::Button::Right
+            )))
+        );
+
+        // released after Control
+        input.convert(
+            &WindowEvent::ModifiersChanged(ModifiersState::default()),
+            1.0,
+            1.0,
+            false,
+        );
+        assert_eq!(
+            input.convert(&left_button(ElementState::Released), 1.0, 1.0, false),
+            Some(Event::Mouse(mouse::Event::ButtonReleased(
+                mouse::Button::Right
+            )))
+        );
+    }
+
+    #[test]
+    fn queues_fewer_moves_during_a_drag() {
+        let mut input = Input::default();
+        let mut queued = Vec::new();
+
+        let mut events = vec![cursor_moved(0.0, 0.0), left_button(ElementState::Pressed)];
+        events.extend((1..=8).map(|x| cursor_moved(x as f64 * 10.0, 0.0)));
+        events.push(left_button(ElementState::Released));
+
+        // like a ChildWindow queues them
+        for event in &events {
+            if let Some(event) = input.convert(event, 1.0, 1.0, false) {
+                if let Some(event) = input.coalesce(event) {
+                    queued.extend(input.take_cursor_moved());
+                    queued.push(event);
+                }
+            }
+        }
+        queued.extend(input.take_cursor_moved());
+
+        assert_eq!(
+            queued,
+            vec![
+                Event::Mouse(mouse::Event::CursorMoved { x: 0.0, y: 0.0 }),
+                Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
+                Event::Mouse(mouse::Event::CursorMoved { x: 80.0, y: 0.0 }),
+                Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)),
+            ]
+        );
+        assert_eq!(
+            input.take_coalesced_cursor_moves(),
+            events.len() - queued.len()
+        );
+        assert_eq!(input.take_coalesced_cursor_moves(), 0);
+    }
+
+    #[test]
+    fn clears_the_modifiers_on_focus_loss() {
+        let mut input = Input::default();
+        input.convert(
+            &WindowEvent::ModifiersChanged(ModifiersState::LOGO),
+            1.0,
+            1.0,
+            false,
+        );
+        assert!(input.modifiers().logo());
+
+        input.convert(&WindowEvent::Focused(false), 1.0, 1.0, false);
+        assert_eq!(input.modifiers(), ModifiersState::default());
+
+        assert_eq!(
+            input.convert(&key_pressed(VirtualKeyCode::A), 1.0, 1.0, false),
+            Some(Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::A,
+                modifiers: keyboard::ModifiersState::default(),
+            }))
+        );
+    }
+
+    #[test]
+    fn scrolls_by_the_lines_of_a_wheel() {
+        let event = Input::default().convert(
+            &mouse_wheel(MouseScrollDelta::LineDelta(0.0, -3.0)),
+            2.0,
+            0.5,
+            false,
+        );
+
+        assert_eq!(
+            event,
+            Some(Event::Mouse(mouse::Event::WheelScrolled {
+                delta: mouse::ScrollDelta::Lines { x: 0.0, y: -3.0 },
+            }))
+        );
+    }
+
+    #[test]
+    fn scales_the_pixels_of_a_trackpad() {
+        let event = Input::default().convert(
+            &mouse_wheel(MouseScrollDelta::PixelDelta(LogicalPosition::new(
+                4.0, -10.0,
+            ))),
+            2.0,
+            0.5,
+            false,
+        );
+
+        assert_eq!(
+            event,
+            Some(Event::Mouse(mouse::Event::WheelScrolled {
+                delta: mouse::ScrollDelta::Pixels { x: 2.0, y: -5.0 },
+            }))
+        );
+    }
+}
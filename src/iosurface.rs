@@ -0,0 +1,83 @@
+use iced_wgpu::{wgpu, Renderer};
+use iced_winit::{Program, Size};
+
+use crate::cocoa_util::{self, IOSurfaceRef};
+use crate::OffscreenRenderer;
+
+/// Renders an iced program into an `IOSurface` the host composites itself, e.g. as the contents
+/// of a layer of its own, instead of into a view. See
+/// [`ChildWindowBuilder::build_iosurface`].
+///
+/// wgpu 0.5 can't wrap the surface in a texture, it renders only into the ones it has created,
+/// so each frame is rendered into a texture and copied into the surface through the CPU.
+///
+/// [`ChildWindowBuilder::build_iosurface`]: struct.ChildWindowBuilder.html#method.build_iosurface
+pub struct IOSurfaceTarget<P: 'static + Program<Renderer = Renderer>> {
+    renderer: OffscreenRenderer<P>,
+    surface: IOSurfaceRef,
+    size: Size<u32>,
+}
+
+impl<P: 'static + Program<Renderer = Renderer>> IOSurfaceTarget<P> {
+    pub(crate) fn new(mut renderer: OffscreenRenderer<P>, size: Size<u32>) -> Self {
+        // an IOSurface can't be empty
+        let size = Size::new(size.width.max(1), size.height.max(1));
+        renderer.set_size(size);
+
+        IOSurfaceTarget {
+            renderer,
+            surface: create_surface(size),
+            size,
+        }
+    }
+
+    /// The surface holding the last rendered frame, in BGRA pixels.
+    ///
+    /// It's replaced on [`resize`]. Retain it to keep it after that or after the target is
+    /// dropped.
+    ///
+    /// [`resize`]: #method.resize
+    pub fn surface(&self) -> IOSurfaceRef {
+        self.surface
+    }
+
+    /// The renderer of the program, e.g. to queue the events of the host or to change the scale
+    /// factor.
+    pub fn renderer(&mut self) -> &mut OffscreenRenderer<P> {
+        &mut self.renderer
+    }
+
+    /// Replaces the surface with one of the physical `size`, which the next frame is rendered
+    /// into.
+    pub fn resize(&mut self, size: Size<u32>) {
+        let size = Size::new(size.width.max(1), size.height.max(1));
+
+        if size != self.size {
+            unsafe { cocoa_util::release_iosurface(self.surface) };
+            self.surface = create_surface(size);
+            self.size = size;
+        }
+    }
+
+    /// Updates the program with the queued events and messages and renders it into the surface.
+    pub fn render(&mut self) -> Result<(), wgpu::BufferAsyncErr> {
+        let pixels = self.renderer.render_to_buffer(self.size)?;
+
+        unsafe {
+            cocoa_util::write_iosurface(self.surface, &pixels, self.size.width, self.size.height)
+        };
+
+        Ok(())
+    }
+}
+
+impl<P: 'static + Program<Renderer = Renderer>> Drop for IOSurfaceTarget<P> {
+    fn drop(&mut self) {
+        unsafe { cocoa_util::release_iosurface(self.surface) };
+    }
+}
+
+fn create_surface(size: Size<u32>) -> IOSurfaceRef {
+    unsafe { cocoa_util::create_iosurface(size.width, size.height) }
+        .expect("the IOSurface of a non-zero size can be created")
+}
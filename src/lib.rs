@@ -0,0 +1,74 @@
+#[cfg(feature = "clap")]
+pub mod clap;
+#[cfg(target_os = "macos")]
+pub mod cocoa_util;
+pub mod controls;
+#[cfg(feature = "vst3")]
+pub mod vst3;
+
+mod accessibility;
+mod background;
+mod builder;
+mod capture;
+mod child_window;
+mod clipboard;
+mod color;
+mod cursor;
+mod damage;
+mod display_link;
+mod error;
+mod file_drop;
+mod filter;
+mod format;
+mod gpu;
+mod harness;
+mod hosted;
+mod input;
+#[cfg(all(target_os = "macos", feature = "iosurface"))]
+mod iosurface;
+mod magnify;
+mod message;
+mod metal_layer;
+mod offscreen;
+mod parent;
+#[cfg(feature = "testing")]
+mod primitives;
+#[cfg(feature = "json")]
+mod recording;
+mod redraw;
+mod resize;
+mod runtime;
+mod schedule;
+mod stats;
+mod theme;
+
+pub use accessibility::{AccessibilityNode, AccessibilityRole};
+pub use builder::ChildWindowBuilder;
+pub use child_window::{ChildWindow, DefaultChildWindow};
+pub use clipboard::{ClipboardProvider, NoClipboard};
+pub use color::linear_color;
+pub use cursor::CursorRequest;
+pub use error::{CaptureError, EmbedError, InitError};
+pub use file_drop::FileDrop;
+pub use filter::Filter;
+pub use gpu::GpuInfo;
+pub use harness::TestHarness;
+pub use message::MessageSender;
+pub use metal_layer::MetalLayerOptions;
+pub use offscreen::{BenchReport, OffscreenRenderer};
+pub use parent::ParentSurface;
+#[cfg(feature = "testing")]
+pub use primitives::PrimitiveSummary;
+#[cfg(feature = "json")]
+pub use recording::{EventRecorder, RecordedEvent};
+pub use redraw::RedrawHandle;
+pub use schedule::{RenderSchedule, FALLBACK_FPS};
+pub use stats::FrameStats;
+pub use theme::Theme;
+
+#[cfg(all(target_os = "macos", feature = "iosurface"))]
+pub use iosurface::IOSurfaceTarget;
+#[cfg(target_os = "macos")]
+pub use parent::CocoaParent;
+#[cfg(target_os = "windows")]
+pub use parent::Win32Parent;
@@ -0,0 +1,154 @@
+//! Receives the magnification of the pinches on a trackpad, which winit 0.22 doesn't report.
+
+#[cfg(target_os = "macos")]
+pub(crate) use self::macos::MagnifyRecognizer;
+
+/// A step of a pinch on the view, in points measured from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Magnification {
+    /// The change of the magnification since the last step, e.g. 0.1 for 10 % larger.
+    pub(crate) delta: f64,
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+}
+
+/// A stub for the platforms without the gestures of a trackpad, it's never created.
+#[cfg(not(target_os = "macos"))]
+pub(crate) struct MagnifyRecognizer;
+
+#[cfg(not(target_os = "macos"))]
+impl MagnifyRecognizer {
+    pub(crate) fn take_magnifications(&self) -> Vec<Magnification> {
+        Vec::new()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::sync::Once;
+
+    use cocoa::base::id;
+    use objc::{
+        runtime::{Class, Object, Sel},
+        sel, sel_impl,
+    };
+
+    use super::Magnification;
+    use crate::cocoa_util;
+
+    thread_local! {
+        // the steps of each recognizer since they were taken last and the magnification of its
+        // gesture so far, by its address
+        static MAGNIFICATIONS: RefCell<HashMap<usize, (f64, Vec<Magnification>)>> =
+            RefCell::new(HashMap::new());
+    }
+
+    /// Recognizes the pinches on a view with an `NSMagnificationGestureRecognizer`.
+    ///
+    /// It's added to the view itself, so it works while the view is attached to a parent too,
+    /// and it doesn't delay the clicks.
+    pub(crate) struct MagnifyRecognizer {
+        view: id,
+        recognizer: id,
+        target: id,
+    }
+
+    impl MagnifyRecognizer {
+        /// Adds a recognizer to the `view`.
+        ///
+        /// # Safety
+        ///
+        /// `view` must be a valid `NSView`, which outlives the recognizer, and it must be called
+        /// on the main thread.
+        pub(crate) unsafe fn new(view: id) -> Self {
+            let target = cocoa_util::create_object(target_class());
+            let recognizer = cocoa_util::add_magnification_recognizer(view, target, sel!(magnify:));
+
+            MAGNIFICATIONS.with(|magnifications| {
+                magnifications
+                    .borrow_mut()
+                    .insert(recognizer as usize, (0.0, Vec::new()))
+            });
+
+            MagnifyRecognizer {
+                view,
+                recognizer,
+                target,
+            }
+        }
+
+        /// The steps of the pinches since the last call.
+        pub(crate) fn take_magnifications(&self) -> Vec<Magnification> {
+            MAGNIFICATIONS.with(|magnifications| {
+                magnifications
+                    .borrow_mut()
+                    .get_mut(&(self.recognizer as usize))
+                    .map(|(_, magnifications)| magnifications.split_off(0))
+                    .unwrap_or_default()
+            })
+        }
+    }
+
+    impl Drop for MagnifyRecognizer {
+        fn drop(&mut self) {
+            // the recognizer doesn't retain its target
+            unsafe {
+                cocoa_util::remove_gesture_recognizer(self.view, self.recognizer);
+                cocoa_util::release(self.target);
+            }
+
+            MAGNIFICATIONS.with(|magnifications| {
+                magnifications
+                    .borrow_mut()
+                    .remove(&(self.recognizer as usize))
+            });
+        }
+    }
+
+    /// The class of the targets of the recognizers, declared on the first call, once per binary
+    /// like the one of the drop targets.
+    fn target_class() -> &'static Class {
+        static DECLARE: Once = Once::new();
+        static mut CLASS: Option<&'static Class> = None;
+
+        DECLARE.call_once(|| unsafe {
+            let name = format!(
+                "IcedChildWinMagnifyTarget{:x}",
+                &DECLARE as *const Once as usize
+            );
+            CLASS = Some(
+                cocoa_util::declare_target_class(&name, sel!(magnify:), magnify)
+                    .expect("the magnify target class is declared only once per binary"),
+            );
+        });
+
+        unsafe { CLASS.expect("the magnify target class is declared") }
+    }
+
+    extern "C" fn magnify(_: &Object, _: Sel, recognizer: id) {
+        let (began, magnification, position) = unsafe { cocoa_util::magnification(recognizer) };
+
+        MAGNIFICATIONS.with(|magnifications| {
+            if let Some((last, magnifications)) =
+                magnifications.borrow_mut().get_mut(&(recognizer as usize))
+            {
+                // the magnification accumulates over a gesture
+                if began {
+                    *last = 0.0;
+                }
+
+                magnifications.push(Magnification {
+                    delta: magnification - *last,
+                    x: position.x,
+                    y: position.y,
+                });
+                *last = magnification;
+            }
+        });
+
+        // the gesture runs in the run loop of AppKit, winit has to run an iteration to deliver it
+        cocoa_util::wake_main_run_loop();
+    }
+}
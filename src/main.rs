@@ -1,215 +1,275 @@
-mod controls;
+#[cfg(target_os = "macos")]
+use std::collections::HashMap;
 
-use cocoa::appkit::{NSApp, NSBackingStoreType, NSView, NSWindow, NSWindowStyleMask};
-use cocoa::base::{id, nil};
+#[cfg(target_os = "macos")]
+use cocoa::base::id;
+#[cfg(target_os = "macos")]
 use cocoa::foundation::{NSPoint, NSRect, NSSize};
 
-use iced_wgpu::{wgpu, Backend, Renderer, Settings, Viewport};
-use iced_winit::{futures, program, winit, Debug, Size};
+#[cfg(target_os = "macos")]
+use iced_wgpu::wgpu;
+#[cfg(target_os = "macos")]
+use iced_winit::{winit, Size};
 
+#[cfg(target_os = "macos")]
 use winit::{
-    event::{Event, ModifiersState, WindowEvent},
+    event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     platform::desktop::EventLoopExtDesktop,
-    platform::macos::{ActivationPolicy, WindowBuilderExtMacOS, WindowExtMacOS},
 };
 
-use controls::Controls;
+#[cfg(target_os = "macos")]
+use iced_child_win::{
+    cocoa_util::{self, Decorations},
+    controls::{Controls, Message},
+    ChildWindowBuilder, CocoaParent, DefaultChildWindow, InitError, ParentSurface,
+};
 
-pub fn main() {
-    env_logger::init();
+/// A parent window hosting a child window, like an editor of a plugin instance.
+#[cfg(target_os = "macos")]
+struct Editor {
+    parent_window: id,
+    parent: CocoaParent,
+    parent_frame: Size,
+    child_window: DefaultChildWindow,
+}
 
-    let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(500.0, 400.0));
-    let parent_window = unsafe {
-        NSWindow::alloc(nil).initWithContentRect_styleMask_backing_defer_(
-            frame,
-            NSWindowStyleMask::NSBorderlessWindowMask | NSWindowStyleMask::NSTitledWindowMask,
-            NSBackingStoreType::NSBackingStoreBuffered,
-            0,
-        )
-    };
-    // this fixes mouse hover
-    unsafe { parent_window.setAcceptsMouseMovedEvents_(1) };
-
-    // Initialize winit
-    let mut event_loop = EventLoop::new();
-    let window = winit::window::WindowBuilder::new()
-        // .with_activation_policy(ActivationPolicy::Prohibited)
-        .with_visible(true)
-        .build(&event_loop)
-        .unwrap();
-
-    unsafe {
-        NSWindow::setFrame_display_(window.ns_window() as id, frame, 0);
-        let child = window.ns_view() as id;
-        // NSView::setFrameSize(child, frame.size);
-        // NSView::setFrameOrigin(child, frame.origin);
-        parent_window.contentView().addSubview_(child);
-    };
+#[cfg(target_os = "macos")]
+impl Editor {
+    fn open(event_loop: &EventLoop<Message>, origin: NSPoint) -> Option<Self> {
+        let frame = NSRect::new(origin, NSSize::new(500.0, 400.0));
+        let parent_window =
+            unsafe { cocoa_util::create_window(frame, Decorations::TitledResizable) };
+        // this fixes mouse hover
+        unsafe { cocoa_util::set_accepts_mouse_moved(parent_window, true) };
 
-    let physical_size = window.inner_size();
-    let mut viewport = Viewport::with_physical_size(
-        Size::new(physical_size.width, physical_size.height),
-        window.scale_factor(),
-    );
-    let mut modifiers = ModifiersState::default();
-
-    // Initialize wgpu
-    let surface = wgpu::Surface::create(&window);
-    let (mut device, queue) = futures::executor::block_on(async {
-        let adapter = wgpu::Adapter::request(
-            &wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::Default,
-                compatible_surface: Some(&surface),
-            },
-            wgpu::BackendBit::PRIMARY,
-        )
-        .await
-        .expect("Request adapter");
-
-        adapter
-            .request_device(&wgpu::DeviceDescriptor {
-                extensions: wgpu::Extensions {
-                    anisotropic_filtering: false,
-                },
-                limits: wgpu::Limits::default(),
-            })
-            .await
-    });
+        let parent = unsafe { CocoaParent::new(parent_window) };
 
-    let format = wgpu::TextureFormat::Bgra8UnormSrgb;
-
-    let mut swap_chain = {
-        let size = window.inner_size();
-
-        device.create_swap_chain(
-            &surface,
-            &wgpu::SwapChainDescriptor {
-                usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-                format,
-                width: size.width,
-                height: size.height,
-                present_mode: wgpu::PresentMode::Mailbox,
-            },
-        )
-    };
-    let mut resized = false;
+        let child_window = match DefaultChildWindow::new(event_loop, &parent, Controls::new()) {
+            // e.g. in a virtual machine without Metal
+            Err(InitError::NoAdapter { .. }) => {
+                tracing::warn!("No Metal adapter, falling back to the secondary backends");
+
+                ChildWindowBuilder::new(Controls::new())
+                    .backend(wgpu::BackendBit::SECONDARY)
+                    .build(event_loop, &parent)
+            }
+            child_window => child_window,
+        };
+
+        let mut child_window = match child_window {
+            Ok(child_window) => child_window,
+            Err(error) => {
+                tracing::error!("{}", error);
+                unsafe { cocoa_util::close_window(parent_window) };
+                return None;
+            }
+        };
+
+        child_window.sync_clear_color(Controls::background_color);
+        child_window.set_theme_message(Message::ThemeChanged);
+        child_window.set_tab_message(Message::FocusMoved);
+        child_window.set_key_message(Controls::key_message);
+        child_window.set_accessibility(Controls::accessibility);
 
-    // Initialize GUI controls
-    let controls = Controls::new();
+        unsafe { cocoa_util::order_front(parent_window) };
 
-    // Initialize iced
-    let mut debug = Debug::new();
-    let mut renderer = Renderer::new(Backend::new(&mut device, Settings::default()));
+        Some(Editor {
+            parent_window,
+            parent_frame: parent.frame(),
+            parent,
+            child_window,
+        })
+    }
+
+    fn close(self) {
+        let Editor {
+            parent_window,
+            child_window,
+            ..
+        } = self;
+
+        // detach the child view before the parent goes away
+        drop(child_window);
+        unsafe { cocoa_util::close_window(parent_window) };
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn main() {
+    env_logger::init();
 
-    let mut state =
-        program::State::new(controls, viewport.logical_size(), &mut renderer, &mut debug);
+    if std::env::args().any(|arg| arg == "--standalone") {
+        run_standalone();
+        return;
+    }
 
-    let mut is_close = false;
+    let mut event_loop = EventLoop::with_user_event();
+    let mut editors = HashMap::new();
 
-    unsafe { parent_window.orderFront_(NSApp()) };
+    // e.g. two instances of the plugin with their editors open
+    for (index, origin) in [NSPoint::new(0.0, 0.0), NSPoint::new(540.0, 0.0)]
+        .iter()
+        .enumerate()
+    {
+        if let Some(editor) = Editor::open(&event_loop, *origin) {
+            editor
+                .child_window
+                .set_parent_title(&format!("iced child window {}", index + 1));
+            editors.insert(editor.child_window.window().id(), editor);
+        }
+    }
 
-    while !is_close {
+    while !editors.is_empty() {
         // Run event loop
-        // in a real application you would call it inside idle function 
+        // in a real application you would call it inside idle function
         event_loop.run_return(|event, _, control_flow| {
             match event {
-                Event::WindowEvent { event, .. } => {
-                    match event {
-                        WindowEvent::ModifiersChanged(new_modifiers) => {
-                            modifiers = new_modifiers;
-                        }
-                        WindowEvent::Resized(new_size) => {
-                            viewport = Viewport::with_physical_size(
-                                Size::new(new_size.width, new_size.height),
-                                window.scale_factor(),
-                            );
+                Event::WindowEvent { window_id, event } => {
+                    if let WindowEvent::CloseRequested = event {
+                        // the program may veto it, e.g. to confirm discarding its changes
+                        let close = editors
+                            .get_mut(&window_id)
+                            .map_or(false, |editor| editor.child_window.close_requested());
 
-                            resized = true;
+                        if close {
+                            if let Some(editor) = editors.remove(&window_id) {
+                                editor.close();
+                            }
                         }
-                        WindowEvent::CloseRequested => {
-                            is_close = true;
+
+                        if editors.is_empty() {
                             *control_flow = ControlFlow::Exit;
                         }
 
-                        _ => {}
+                        return;
                     }
 
-                    // Map window event to iced event
-                    if let Some(event) = iced_winit::conversion::window_event(
-                        &event,
-                        window.scale_factor(),
-                        modifiers,
-                    ) {
-                        state.queue_event(event);
+                    if let Some(editor) = editors.get_mut(&window_id) {
+                        editor.child_window.process_event(&event);
+                    }
+                }
+                // the knobs grabbing the cursor are dragged by the mouse motion on macOS
+                Event::DeviceEvent { event, .. } => {
+                    for editor in editors.values_mut() {
+                        editor.child_window.process_device_event(&event);
                     }
                 }
                 Event::MainEventsCleared => {
-                    // We update iced
-                    let _ = state.update(None, viewport.logical_size(), &mut renderer, &mut debug);
+                    for editor in editors.values_mut() {
+                        // the host may resize the parent at any time
+                        let frame = editor.parent.frame();
 
-                    // and request a redraw
-                    window.request_redraw();
+                        if frame != editor.parent_frame {
+                            editor.parent_frame = frame;
+                            editor.child_window.resize(frame);
+                        }
+
+                        editor.child_window.update();
+                    }
                 }
-                Event::RedrawRequested(_) => {
-                    if resized {
-                        let size = window.inner_size();
-
-                        swap_chain = device.create_swap_chain(
-                            &surface,
-                            &wgpu::SwapChainDescriptor {
-                                usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-                                format,
-                                width: size.width,
-                                height: size.height,
-                                present_mode: wgpu::PresentMode::Mailbox,
-                            },
-                        );
+                Event::RedrawRequested(window_id) => {
+                    if let Some(editor) = editors.get_mut(&window_id) {
+                        editor.child_window.render();
                     }
-
-                    let frame = swap_chain.get_next_texture().expect("Next frame");
-
-                    let mut encoder = device
-                        .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-
-                    let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                        color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                            attachment: &frame.view,
-                            resolve_target: None,
-                            load_op: wgpu::LoadOp::Clear,
-                            store_op: wgpu::StoreOp::Store,
-                            clear_color: wgpu::Color {
-                                r: 1.0,
-                                g: 0.5,
-                                b: 0.0,
-                                a: 1.0,
-                            },
-                        }],
-                        depth_stencil_attachment: None,
-                    });
-
-                    // And then iced on top
-                    let mouse_interaction = renderer.backend_mut().draw(
-                        &mut device,
-                        &mut encoder,
-                        &frame.view,
-                        &viewport,
-                        state.primitive(),
-                        &debug.overlay(),
-                    );
-
-                    // Then we submit the work
-                    queue.submit(&[encoder.finish()]);
-
-                    // And update the mouse cursor
-                    window.set_cursor_icon(iced_winit::conversion::mouse_interaction(
-                        mouse_interaction,
-                    ));
                 }
-                // we use Poll instead of Wait, because we can't pause the thread on Plugin::idle
-                // and Plugin::idle does its own optimizations
-                _ => *control_flow = ControlFlow::Poll,
+                // sent with a MessageSender, every editor shows the same parameters
+                Event::UserEvent(message) => {
+                    for editor in editors.values_mut() {
+                        editor.child_window.queue_message(message.clone());
+                    }
+                }
+                // we own the event loop, so we can wait for the next frame, but in a plugin we
+                // would use Poll, because we can't pause the thread on Plugin::idle and
+                // Plugin::idle does its own optimizations
+                _ => *control_flow = control_flow_of(editors.values()),
             }
         });
     }
 }
+
+/// The control flow satisfying all the `editors`: the one waking up the soonest.
+#[cfg(target_os = "macos")]
+fn control_flow_of<'a>(editors: impl Iterator<Item = &'a Editor>) -> ControlFlow {
+    editors.fold(ControlFlow::Wait, |control_flow, editor| {
+        match (control_flow, editor.child_window.control_flow()) {
+            (ControlFlow::Poll, _) | (_, ControlFlow::Poll) => ControlFlow::Poll,
+            (ControlFlow::WaitUntil(a), ControlFlow::WaitUntil(b)) => {
+                ControlFlow::WaitUntil(a.min(b))
+            }
+            (ControlFlow::WaitUntil(instant), _) | (_, ControlFlow::WaitUntil(instant)) => {
+                ControlFlow::WaitUntil(instant)
+            }
+            _ => control_flow,
+        }
+    })
+}
+
+/// Runs the GUI in a top-level window of its own, without a parent.
+fn run_standalone() {
+    use iced_child_win::{
+        controls::{Controls, Message},
+        DefaultChildWindow,
+    };
+    use iced_winit::winit::{
+        event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+        event_loop::{ControlFlow, EventLoop},
+    };
+
+    let event_loop = EventLoop::<Message>::with_user_event();
+
+    let mut child_window = match DefaultChildWindow::standalone(&event_loop, Controls::new()) {
+        Ok(child_window) => child_window,
+        Err(error) => {
+            tracing::error!("{}", error);
+            return;
+        }
+    };
+
+    child_window.sync_clear_color(Controls::background_color);
+    child_window.set_theme_message(Message::ThemeChanged);
+    child_window.set_tab_message(Message::FocusMoved);
+    child_window.set_key_message(Controls::key_message);
+    child_window.set_parent_title("iced child window");
+
+    event_loop.run(move |event, _, control_flow| match event {
+        Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } => {
+            if child_window.close_requested() {
+                *control_flow = ControlFlow::Exit;
+            }
+        }
+        Event::WindowEvent {
+            event:
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::F11),
+                            ..
+                        },
+                    ..
+                },
+            ..
+        } => {
+            let fullscreen = !child_window.is_fullscreen();
+            child_window.set_fullscreen(fullscreen);
+        }
+        Event::WindowEvent { event, .. } => child_window.process_event(&event),
+        Event::DeviceEvent { event, .. } => child_window.process_device_event(&event),
+        Event::UserEvent(message) => child_window.queue_message(message),
+        Event::MainEventsCleared => child_window.update(),
+        Event::RedrawRequested(_) => child_window.render(),
+        _ => *control_flow = child_window.control_flow(),
+    });
+}
+
+/// Without cocoa to create the parents, the GUI runs standalone.
+#[cfg(not(target_os = "macos"))]
+pub fn main() {
+    env_logger::init();
+
+    run_standalone();
+}
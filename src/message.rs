@@ -0,0 +1,74 @@
+use std::pin::Pin;
+
+use iced_winit::futures::{
+    channel::mpsc,
+    task::{Context, Poll},
+    Sink,
+};
+use iced_winit::winit::event_loop::{EventLoop, EventLoopClosed, EventLoopProxy};
+
+/// Sends messages to the program hosted by a [`ChildWindow`] from other threads, e.g. the audio
+/// thread of a plugin.
+///
+/// The messages arrive as `Event::UserEvent` to the event loop the sender was created for, which
+/// is woken up for each one. Pass them to [`ChildWindow::queue_message`].
+///
+/// The sender is `Send`, but not `Sync`: clone it for each thread sending messages. Sending
+/// never blocks, so it's fine to call it from a real-time thread once the sender is cloned.
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+/// [`ChildWindow::queue_message`]: struct.ChildWindow.html#method.queue_message
+pub struct MessageSender<M: 'static> {
+    proxy: EventLoopProxy<M>,
+}
+
+impl<M: 'static> MessageSender<M> {
+    /// Creates a sender of messages to the `event_loop`.
+    pub fn new(event_loop: &EventLoop<M>) -> Self {
+        MessageSender {
+            proxy: event_loop.create_proxy(),
+        }
+    }
+
+    /// Sends the `message` and wakes up the event loop.
+    ///
+    /// Returns the `message` back if the event loop doesn't exist anymore.
+    pub fn send(&self, message: M) -> Result<(), EventLoopClosed<M>> {
+        self.proxy.send_event(message)
+    }
+}
+
+/// The runtime of a window built [`with_runtime`] sends the messages of the commands and
+/// subscriptions through it.
+///
+/// [`with_runtime`]: struct.ChildWindowBuilder.html#method.with_runtime
+impl<M: 'static> Sink<M> for MessageSender<M> {
+    type Error = mpsc::SendError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, message: M) -> Result<(), Self::Error> {
+        // the window goes with the event loop, nobody is left to handle the message
+        let _ = self.send(message);
+
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<M: 'static> Clone for MessageSender<M> {
+    fn clone(&self) -> Self {
+        MessageSender {
+            proxy: self.proxy.clone(),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use iced_winit::winit::window::Window;
+
+#[cfg(target_os = "macos")]
+use cocoa::base::id;
+#[cfg(target_os = "macos")]
+use iced_winit::winit::platform::macos::WindowExtMacOS;
+
+#[cfg(target_os = "macos")]
+use crate::cocoa_util;
+
+/// The properties of the `CAMetalLayer` backing the view, see
+/// [`ChildWindowBuilder::metal_layer`].
+///
+/// Each `None` keeps what wgpu configures the layer with for the swap chain, which is the
+/// default. They're ignored on the other platforms.
+///
+/// [`ChildWindowBuilder::metal_layer`]: struct.ChildWindowBuilder.html#method.metal_layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MetalLayerOptions {
+    /// Whether the drawables can only be rendered to. wgpu makes them framebuffer-only, which
+    /// renders fastest. `false` lets a host compositing with Core Animation sample them, e.g.
+    /// for a filter on our layer.
+    pub framebuffer_only: Option<bool>,
+    /// Whether the frames are presented on the vertical blank. wgpu disables it only for the
+    /// `Immediate` present mode. Disabling it lowers the latency, but the frames may tear.
+    pub display_sync_enabled: Option<bool>,
+    /// How many drawables the layer cycles through, 2 or 3. 2 lowers the latency by a frame,
+    /// but a frame that misses the vertical blank stalls the next one.
+    pub maximum_drawable_count: Option<u32>,
+    /// Whether the frames are presented within the Core Animation transaction of the main
+    /// thread, which wgpu doesn't do. Enable it for a host that resizes its own layers along with
+    /// ours, so they don't get out of sync during a live resize. Presenting waits for the frame
+    /// to be scheduled then.
+    pub presents_with_transaction: Option<bool>,
+}
+
+/// Applies the `options` to the layer of the view of the `window`.
+///
+/// wgpu configures the layer with each swap chain, so it's called after each of them is created.
+#[cfg(target_os = "macos")]
+pub(crate) fn configure(window: &Window, options: &MetalLayerOptions) {
+    unsafe { cocoa_util::configure_metal_layer(window.ns_view() as id, options) };
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn configure(_window: &Window, _options: &MetalLayerOptions) {}
@@ -0,0 +1,296 @@
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "testing")]
+use iced_wgpu::Primitive;
+use iced_wgpu::{wgpu, Backend, Renderer, Settings, Viewport};
+use iced_winit::{program, window, Command, Debug, Event, Program, Size};
+
+use crate::capture;
+use crate::clipboard;
+use crate::gpu;
+use crate::{ClipboardProvider, InitError, NoClipboard};
+
+/// The format of the rendered pixels.
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// The timings of [`OffscreenRenderer::bench`].
+///
+/// It's plain data, so it can be logged, or serialized with the `json` feature for regression
+/// tracking.
+///
+/// [`OffscreenRenderer::bench`]: struct.OffscreenRenderer.html#method.bench
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct BenchReport {
+    /// How many frames were rendered.
+    pub frames: usize,
+    /// How long all the frames took.
+    pub total_time: Duration,
+    /// The shortest frame.
+    pub min_frame_time: Duration,
+    /// The longest frame.
+    pub max_frame_time: Duration,
+    /// The mean frame.
+    pub mean_frame_time: Duration,
+    /// How long the updates of the program took in total, including the layout.
+    pub update_time: Duration,
+    /// How long the submissions took in total, including waiting for the GPU.
+    pub submit_time: Duration,
+}
+
+/// Renders an iced program into a texture instead of a window, e.g. in tests.
+///
+/// It needs neither a parent window nor an event loop. A host compositing the editor itself can
+/// copy the pixels of [`render_to_buffer`], or have them copied into an `IOSurface` with the
+/// `iosurface` feature on macOS, see [`ChildWindowBuilder::build_iosurface`].
+///
+/// [`render_to_buffer`]: #method.render_to_buffer
+/// [`ChildWindowBuilder::build_iosurface`]: struct.ChildWindowBuilder.html#method.build_iosurface
+pub struct OffscreenRenderer<P: 'static + Program<Renderer = Renderer>> {
+    state: program::State<P>,
+    renderer: Renderer,
+    queue: wgpu::Queue,
+    device: wgpu::Device,
+    debug: Debug,
+    size: Size<u32>,
+    scale_factor: f64,
+    clear_color: wgpu::Color,
+    clipboard: Box<dyn ClipboardProvider>,
+}
+
+impl<P: 'static + Program<Renderer = Renderer>> OffscreenRenderer<P> {
+    /// Creates a renderer of the `program`.
+    pub fn new(program: P) -> Result<Self, InitError> {
+        Self::with_backends(program, wgpu::BackendBit::PRIMARY)
+    }
+
+    /// Creates a renderer of the `program` with an adapter of the `backends`, e.g. to test the
+    /// rendering of each of them.
+    pub fn with_backends(program: P, backends: wgpu::BackendBit) -> Result<Self, InitError> {
+        let (_, mut device, queue) = gpu::request(
+            wgpu::PowerPreference::LowPower,
+            backends,
+            false,
+            &wgpu::Limits::default(),
+            None,
+        )?;
+
+        let mut debug = Debug::new();
+        let settings = Settings {
+            format: FORMAT,
+            ..Settings::default()
+        };
+        let mut renderer = Renderer::new(Backend::new(&mut device, settings));
+
+        let size = Size::new(1, 1);
+        let scale_factor = 1.0;
+        let viewport = Viewport::with_physical_size(size, scale_factor);
+        let state =
+            program::State::new(program, viewport.logical_size(), &mut renderer, &mut debug);
+
+        Ok(OffscreenRenderer {
+            state,
+            renderer,
+            queue,
+            device,
+            debug,
+            size,
+            scale_factor,
+            clear_color: wgpu::Color::TRANSPARENT,
+            clipboard: Box::new(NoClipboard),
+        })
+    }
+
+    /// The rendered program.
+    pub fn program(&self) -> &P {
+        self.state.program()
+    }
+
+    /// The primitive tree of the last [`update`], which is drawn on the next render, see
+    /// [`PrimitiveSummary`].
+    ///
+    /// [`update`]: #method.update
+    /// [`PrimitiveSummary`]: struct.PrimitiveSummary.html
+    #[cfg(feature = "testing")]
+    pub fn current_primitive(&self) -> &Primitive {
+        &self.state.primitive().0
+    }
+
+    /// The scale factor the program is rendered with.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Queues the `event` to the program. It's handled on the next [`update`].
+    ///
+    /// [`update`]: #method.update
+    pub fn queue_event(&mut self, event: Event) {
+        self.state.queue_event(event);
+    }
+
+    /// Queues the `message` to the program. It's handled on the next [`update`], after the
+    /// messages produced by the queued events.
+    ///
+    /// [`update`]: #method.update
+    pub fn queue_message(&mut self, message: P::Message) {
+        self.state.queue_message(message);
+    }
+
+    /// Sets the physical size the program is laid out and rendered in.
+    pub fn set_size(&mut self, size: Size<u32>) {
+        if size != self.size {
+            self.size = size;
+            self.relayout();
+        }
+    }
+
+    /// Makes the program paste from the `clipboard`, e.g. to test pasting. It holds nothing by
+    /// default.
+    pub fn set_clipboard(&mut self, clipboard: impl ClipboardProvider + 'static) {
+        self.clipboard = Box::new(clipboard);
+    }
+
+    /// Updates the program with the queued events and messages, without rendering it.
+    ///
+    /// Returns the `Command` batching the futures the program returned for the messages,
+    /// `None` if it handled none, e.g. for a test to assert on them or to run them.
+    pub fn update(&mut self) -> Option<Command<P::Message>> {
+        let viewport = Viewport::with_physical_size(self.size, self.scale_factor);
+
+        self.state.update(
+            Some(&clipboard::Bridge(&*self.clipboard)),
+            viewport.logical_size(),
+            &mut self.renderer,
+            &mut self.debug,
+        )
+    }
+
+    /// Sets the linear color the texture is cleared with, see [`linear_color`]. Transparent by
+    /// default.
+    ///
+    /// [`linear_color`]: fn.linear_color.html
+    pub fn set_clear_color(&mut self, color: wgpu::Color) {
+        self.clear_color = color;
+    }
+
+    /// Sets the scale factor the program is rendered with. 1 by default.
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+        self.relayout();
+    }
+
+    /// Updates the program with the queued events and messages and renders it into a texture of
+    /// the physical `size`.
+    ///
+    /// Returns the RGBA pixels of the texture, row by row. They're empty if the width or the
+    /// height is zero, the program is still updated then.
+    pub fn render_to_buffer(&mut self, size: Size<u32>) -> Result<Vec<u8>, wgpu::BufferAsyncErr> {
+        self.set_size(size);
+        self.update();
+
+        // wgpu can't create an empty texture
+        if size.width == 0 || size.height == 0 {
+            return Ok(Vec::new());
+        }
+
+        let texture = capture::create_target(&self.device, size, FORMAT);
+        let encoder = self.draw(&texture.create_default_view());
+
+        capture::read_pixels(&self.device, &self.queue, encoder, &texture, size, FORMAT)
+    }
+
+    /// Renders `frames` frames as fast as possible and measures them. They have the size set
+    /// with [`set_size`].
+    ///
+    /// The program is laid out again every frame, like when it's animated, and every frame waits
+    /// for the GPU, so the frame time includes its work.
+    ///
+    /// [`set_size`]: #method.set_size
+    pub fn bench(&mut self, frames: usize) -> BenchReport {
+        let texture = capture::create_target(&self.device, self.size, FORMAT);
+        let view = texture.create_default_view();
+
+        let mut report = BenchReport {
+            frames,
+            ..BenchReport::default()
+        };
+        let start = Instant::now();
+
+        for frame in 0..frames {
+            let frame_start = Instant::now();
+
+            self.relayout();
+            self.update();
+            let update_time = frame_start.elapsed();
+
+            let encoder = self.draw(&view);
+            let submit_start = Instant::now();
+            self.queue.submit(&[encoder.finish()]);
+            self.device.poll(wgpu::Maintain::Wait);
+            let submit_time = submit_start.elapsed();
+
+            let frame_time = frame_start.elapsed();
+
+            if frame == 0 {
+                report.min_frame_time = frame_time;
+            }
+
+            report.min_frame_time = report.min_frame_time.min(frame_time);
+            report.max_frame_time = report.max_frame_time.max(frame_time);
+            report.update_time += update_time;
+            report.submit_time += submit_time;
+        }
+
+        report.total_time = start.elapsed();
+
+        if frames > 0 {
+            report.mean_frame_time = report.total_time.div_f64(frames as f64);
+        }
+
+        report
+    }
+
+    /// Records the clear pass and the iced draw into the `view`.
+    fn draw(&mut self, view: &wgpu::TextureView) -> wgpu::CommandEncoder {
+        let viewport = Viewport::with_physical_size(self.size, self.scale_factor);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: view,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Clear,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: self.clear_color,
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        let _ = self.renderer.backend_mut().draw(
+            &mut self.device,
+            &mut encoder,
+            view,
+            &viewport,
+            self.state.primitive(),
+            &self.debug.overlay(),
+        );
+
+        encoder
+    }
+
+    /// Makes the program lay out for the new size or scale factor on the next update, because it
+    /// lays out only when it receives events.
+    fn relayout(&mut self) {
+        let logical_size =
+            Viewport::with_physical_size(self.size, self.scale_factor).logical_size();
+
+        self.state
+            .queue_event(Event::Window(window::Event::Resized {
+                width: logical_size.width as u32,
+                height: logical_size.height as u32,
+            }));
+    }
+}
@@ -0,0 +1,174 @@
+use iced_winit::Size;
+use raw_window_handle::RawWindowHandle;
+
+use crate::EmbedError;
+
+#[cfg(target_os = "macos")]
+use cocoa::{appkit::NSWindow, base::id};
+
+#[cfg(target_os = "macos")]
+use crate::cocoa_util;
+
+#[cfg(target_os = "windows")]
+use winapi::shared::windef::HWND;
+
+/// A native window that can host a [`ChildWindow`].
+///
+/// Implement it to embed the child window into parents we don't provide, like X11 windows.
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+pub trait ParentSurface {
+    /// Adds the `child` window to the parent.
+    fn attach_child(&self, child: RawWindowHandle) -> Result<(), EmbedError>;
+
+    /// The logical size of the area the child window should fill.
+    fn frame(&self) -> Size;
+}
+
+/// Creates the parent matching the platform of the `handle`.
+pub(crate) fn from_raw(handle: RawWindowHandle) -> Result<Box<dyn ParentSurface>, EmbedError> {
+    match handle {
+        #[cfg(target_os = "macos")]
+        RawWindowHandle::MacOS(handle) if !handle.ns_window.is_null() => Ok(Box::new(unsafe {
+            CocoaParent::new(handle.ns_window as id)
+        })),
+        #[cfg(target_os = "windows")]
+        RawWindowHandle::Windows(handle) if !handle.hwnd.is_null() => {
+            Ok(Box::new(unsafe { Win32Parent::new(handle.hwnd as HWND) }))
+        }
+        _ => Err(EmbedError::UnsupportedParent),
+    }
+}
+
+/// A cocoa `NSWindow` parent, which hosts the child view in its content view.
+#[cfg(target_os = "macos")]
+pub struct CocoaParent {
+    window: id,
+}
+
+#[cfg(target_os = "macos")]
+impl CocoaParent {
+    /// Wraps the `window`.
+    ///
+    /// # Safety
+    ///
+    /// `window` must be a valid `NSWindow`, which outlives the returned parent.
+    pub unsafe fn new(window: id) -> Self {
+        CocoaParent { window }
+    }
+
+    /// The wrapped `NSWindow`.
+    pub fn window(&self) -> id {
+        self.window
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl ParentSurface for CocoaParent {
+    fn attach_child(&self, child: RawWindowHandle) -> Result<(), EmbedError> {
+        match child {
+            RawWindowHandle::MacOS(handle) => {
+                // the content view excludes the title bar, so the child has to be positioned
+                // within its bounds, not the frame of the window, to get the input where it's
+                // expected
+                unsafe {
+                    cocoa_util::attach_subview(self.window.contentView(), handle.ns_view as id)
+                };
+
+                Ok(())
+            }
+            _ => Err(EmbedError::UnsupportedParent),
+        }
+    }
+
+    fn frame(&self) -> Size {
+        let bounds = unsafe { cocoa_util::content_bounds(self.window) };
+
+        Size::new(bounds.size.width as f32, bounds.size.height as f32)
+    }
+}
+
+/// A Win32 `HWND` parent, which hosts the child as a `WS_CHILD` window.
+#[cfg(target_os = "windows")]
+pub struct Win32Parent {
+    hwnd: HWND,
+}
+
+#[cfg(target_os = "windows")]
+impl Win32Parent {
+    /// Wraps the `hwnd`.
+    ///
+    /// # Safety
+    ///
+    /// `hwnd` must be a valid window handle, which outlives the returned parent.
+    pub unsafe fn new(hwnd: HWND) -> Self {
+        Win32Parent { hwnd }
+    }
+
+    /// The wrapped `HWND`.
+    pub fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl ParentSurface for Win32Parent {
+    fn attach_child(&self, child: RawWindowHandle) -> Result<(), EmbedError> {
+        use std::ptr;
+
+        use winapi::um::winuser::{
+            GetWindowLongPtrW, SetParent, SetWindowLongPtrW, SetWindowPos, GWL_STYLE,
+            SWP_FRAMECHANGED, SWP_NOSIZE, SWP_NOZORDER, WS_CHILD, WS_OVERLAPPEDWINDOW, WS_POPUP,
+        };
+
+        match child {
+            RawWindowHandle::Windows(handle) => unsafe {
+                let child = handle.hwnd as HWND;
+
+                let style = GetWindowLongPtrW(child, GWL_STYLE);
+                let style = (style & !((WS_POPUP | WS_OVERLAPPEDWINDOW) as _)) | WS_CHILD as _;
+                SetWindowLongPtrW(child, GWL_STYLE, style);
+
+                if SetParent(child, self.hwnd).is_null() {
+                    return Err(EmbedError::Os(std::io::Error::last_os_error()));
+                }
+
+                // Windows caches the frame, so it has to be told that the style has changed
+                SetWindowPos(
+                    child,
+                    ptr::null_mut(),
+                    0,
+                    0,
+                    0,
+                    0,
+                    SWP_NOSIZE | SWP_NOZORDER | SWP_FRAMECHANGED,
+                );
+
+                Ok(())
+            },
+            _ => Err(EmbedError::UnsupportedParent),
+        }
+    }
+
+    fn frame(&self) -> Size {
+        use winapi::shared::windef::RECT;
+        use winapi::um::winuser::{GetClientRect, GetDpiForWindow, USER_DEFAULT_SCREEN_DPI};
+
+        let mut rect = RECT {
+            left: 0,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+
+        let scale_factor = unsafe {
+            GetClientRect(self.hwnd, &mut rect);
+            GetDpiForWindow(self.hwnd) as f32 / USER_DEFAULT_SCREEN_DPI as f32
+        };
+
+        Size::new(
+            (rect.right - rect.left) as f32 / scale_factor,
+            (rect.bottom - rect.top) as f32 / scale_factor,
+        )
+    }
+}
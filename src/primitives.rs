@@ -0,0 +1,53 @@
+use iced_wgpu::Primitive;
+
+/// What a primitive tree draws, e.g. to assert in a test that moving a slider changes the text
+/// of its value, without comparing pixels.
+///
+/// The groups, clips, translations and caches are walked through, the nodes they contain are
+/// counted.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PrimitiveSummary {
+    /// The quads, e.g. backgrounds, borders and the rails and handles of sliders.
+    pub quads: usize,
+    /// The content of the texts, in drawing order.
+    pub texts: Vec<String>,
+    /// The raster images.
+    pub images: usize,
+    /// The vector images.
+    pub svgs: usize,
+    /// The meshes, e.g. of a canvas.
+    pub meshes: usize,
+    /// The clipped layers, e.g. of scrollables.
+    pub clips: usize,
+}
+
+impl PrimitiveSummary {
+    /// Summarizes the `primitive` tree.
+    pub fn of(primitive: &Primitive) -> Self {
+        let mut summary = PrimitiveSummary::default();
+        summary.add(primitive);
+        summary
+    }
+
+    fn add(&mut self, primitive: &Primitive) {
+        match primitive {
+            Primitive::Group { primitives } => {
+                for primitive in primitives {
+                    self.add(primitive);
+                }
+            }
+            Primitive::Text { content, .. } => self.texts.push(content.clone()),
+            Primitive::Quad { .. } => self.quads += 1,
+            Primitive::Image { .. } => self.images += 1,
+            Primitive::Svg { .. } => self.svgs += 1,
+            Primitive::Clip { content, .. } => {
+                self.clips += 1;
+                self.add(content);
+            }
+            Primitive::Translate { content, .. } => self.add(content),
+            Primitive::Mesh2D { .. } => self.meshes += 1,
+            Primitive::Cached { cache } => self.add(cache),
+            _ => {}
+        }
+    }
+}
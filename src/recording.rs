@@ -0,0 +1,205 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use iced_winit::winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{
+        DeviceId, ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+        TouchPhase, VirtualKeyCode, WindowEvent,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// A window event an [`EventRecorder`] can record, the ones that drive a GUI.
+///
+/// The positions and sizes are physical, like winit reports them.
+///
+/// [`EventRecorder`]: struct.EventRecorder.html
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    Focused(bool),
+    ModifiersChanged(ModifiersState),
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    CursorEntered,
+    CursorLeft,
+    MouseInput {
+        state: ElementState,
+        button: MouseButton,
+    },
+    MouseWheel {
+        delta: MouseScrollDelta,
+    },
+    KeyboardInput {
+        scancode: u32,
+        state: ElementState,
+        virtual_keycode: Option<VirtualKeyCode>,
+    },
+    ReceivedCharacter(char),
+}
+
+impl RecordedEvent {
+    /// The recordable part of the `event`, if it's recordable.
+    pub fn from_window_event(event: &WindowEvent<'_>) -> Option<Self> {
+        let event = match event {
+            WindowEvent::Resized(size) => RecordedEvent::Resized {
+                width: size.width,
+                height: size.height,
+            },
+            WindowEvent::Focused(focused) => RecordedEvent::Focused(*focused),
+            WindowEvent::ModifiersChanged(modifiers) => RecordedEvent::ModifiersChanged(*modifiers),
+            WindowEvent::CursorMoved { position, .. } => RecordedEvent::CursorMoved {
+                x: position.x,
+                y: position.y,
+            },
+            WindowEvent::CursorEntered { .. } => RecordedEvent::CursorEntered,
+            WindowEvent::CursorLeft { .. } => RecordedEvent::CursorLeft,
+            WindowEvent::MouseInput { state, button, .. } => RecordedEvent::MouseInput {
+                state: *state,
+                button: *button,
+            },
+            WindowEvent::MouseWheel { delta, .. } => RecordedEvent::MouseWheel { delta: *delta },
+            WindowEvent::KeyboardInput { input, .. } => RecordedEvent::KeyboardInput {
+                scancode: input.scancode,
+                state: input.state,
+                virtual_keycode: input.virtual_keycode,
+            },
+            WindowEvent::ReceivedCharacter(character) => {
+                RecordedEvent::ReceivedCharacter(*character)
+            }
+            _ => return None,
+        };
+
+        Some(event)
+    }
+
+    /// The window event to replay, from a dummy device.
+    #[allow(deprecated)]
+    pub fn to_window_event(self) -> WindowEvent<'static> {
+        // the events are replayed without a device, nothing tells them apart
+        let device_id = unsafe { DeviceId::dummy() };
+        // the modifiers of the events are deprecated, ModifiersChanged tracks them
+        let modifiers = ModifiersState::default();
+
+        match self {
+            RecordedEvent::Resized { width, height } => {
+                WindowEvent::Resized(PhysicalSize::new(width, height))
+            }
+            RecordedEvent::Focused(focused) => WindowEvent::Focused(focused),
+            RecordedEvent::ModifiersChanged(modifiers) => WindowEvent::ModifiersChanged(modifiers),
+            RecordedEvent::CursorMoved { x, y } => WindowEvent::CursorMoved {
+                device_id,
+                position: PhysicalPosition::new(x, y),
+                modifiers,
+            },
+            RecordedEvent::CursorEntered => WindowEvent::CursorEntered { device_id },
+            RecordedEvent::CursorLeft => WindowEvent::CursorLeft { device_id },
+            RecordedEvent::MouseInput { state, button } => WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                modifiers,
+            },
+            RecordedEvent::MouseWheel { delta } => WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase: TouchPhase::Moved,
+                modifiers,
+            },
+            RecordedEvent::KeyboardInput {
+                scancode,
+                state,
+                virtual_keycode,
+            } => WindowEvent::KeyboardInput {
+                device_id,
+                input: KeyboardInput {
+                    scancode,
+                    state,
+                    virtual_keycode,
+                    modifiers,
+                },
+                is_synthetic: false,
+            },
+            RecordedEvent::ReceivedCharacter(character) => {
+                WindowEvent::ReceivedCharacter(character)
+            }
+        }
+    }
+}
+
+/// A recorded event and when it happened after the recording started.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TimedEvent {
+    pub(crate) time: Duration,
+    pub(crate) event: RecordedEvent,
+}
+
+/// Records the window events into a file, e.g. to reproduce a bug a user has reported with
+/// [`TestHarness::replay`].
+///
+/// The file has a JSON object per line, with the event and its time.
+///
+/// [`TestHarness::replay`]: struct.TestHarness.html#method.replay
+pub struct EventRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl EventRecorder {
+    /// Creates the recording file at `path`, overwriting it. The times are measured from now.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(EventRecorder {
+            writer: BufWriter::new(File::create(path)?),
+            start: Instant::now(),
+        })
+    }
+
+    /// Records the `event`, unless it isn't a [`RecordedEvent`].
+    ///
+    /// [`RecordedEvent`]: enum.RecordedEvent.html
+    pub fn record(&mut self, event: &WindowEvent<'_>) -> io::Result<()> {
+        let event = match RecordedEvent::from_window_event(event) {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+
+        let timed_event = TimedEvent {
+            time: self.start.elapsed(),
+            event,
+        };
+
+        serde_json::to_writer(&mut self.writer, &timed_event)?;
+        writeln!(self.writer)
+    }
+
+    /// Writes the buffered events to the file. Dropping the recorder flushes it too, but
+    /// ignores the errors.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Reads the events recorded by an [`EventRecorder`] into the file at `path`.
+///
+/// [`EventRecorder`]: struct.EventRecorder.html
+pub(crate) fn read(path: &Path) -> io::Result<Vec<TimedEvent>> {
+    let mut events = Vec::new();
+
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+
+        if !line.trim().is_empty() {
+            events.push(serde_json::from_str(&line)?);
+        }
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::MessageSender;
+
+/// Requests a [`ChildWindow`] to render a frame from another thread, e.g. the UI thread of the
+/// host after the editor has come back from behind another window.
+///
+/// Unlike the window, which must stay on the thread of its event loop, the handle is `Send`,
+/// but not `Sync` like the [`MessageSender`] it wakes the loop with: clone it for each thread.
+/// The request raises a flag and sends the wake-up event, which arrives as `Event::UserEvent`,
+/// then the next [`ChildWindow::update`] takes the flag and requests a redraw. The requests are
+/// coalesced, until the flag is taken only the first one sends the event.
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+/// [`MessageSender`]: struct.MessageSender.html
+/// [`ChildWindow::update`]: struct.ChildWindow.html#method.update
+pub struct RedrawHandle<T: 'static> {
+    requested: Arc<AtomicBool>,
+    sender: MessageSender<T>,
+    wake_up: T,
+}
+
+impl<T: 'static + Clone> RedrawHandle<T> {
+    pub(crate) fn new(requested: Arc<AtomicBool>, sender: MessageSender<T>, wake_up: T) -> Self {
+        RedrawHandle {
+            requested,
+            sender,
+            wake_up,
+        }
+    }
+
+    /// Schedules one frame of the window.
+    pub fn request_redraw(&self) {
+        // wake the loop only once per frame we haven't rendered yet
+        if self.requested.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        // the window goes with the event loop, nothing is left to redraw
+        let _ = self.sender.send(self.wake_up.clone());
+    }
+}
+
+impl<T: 'static + Clone> Clone for RedrawHandle<T> {
+    fn clone(&self) -> Self {
+        RedrawHandle {
+            requested: self.requested.clone(),
+            sender: self.sender.clone(),
+            wake_up: self.wake_up.clone(),
+        }
+    }
+}
+
+impl<T: 'static> fmt::Debug for RedrawHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedrawHandle")
+            .field("requested", &self.requested)
+            .finish()
+    }
+}
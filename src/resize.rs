@@ -0,0 +1,107 @@
+use iced_winit::winit::dpi::PhysicalSize;
+
+/// Debounces the resizes of a [`ChildWindow`], so its swap chain is recreated at most once per
+/// frame however many sizes winit reports during a live resize.
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+#[derive(Debug, Default)]
+pub(crate) struct Resizes {
+    pending: Option<PhysicalSize<u32>>,
+    coalesced: usize,
+    stale: bool,
+}
+
+impl Resizes {
+    /// Replaces the size reported since the last one taken with the `size`.
+    pub(crate) fn report(&mut self, size: PhysicalSize<u32>) {
+        if self.pending.replace(size).is_some() {
+            self.coalesced += 1;
+        }
+    }
+
+    /// Takes the latest size reported since the last one taken.
+    pub(crate) fn take_reported(&mut self) -> Option<PhysicalSize<u32>> {
+        self.pending.take()
+    }
+
+    /// Takes the latest size reported since the last frame, which is applied before the next one,
+    /// and has the swap chain recreated for it then.
+    pub(crate) fn flush(&mut self) -> Option<PhysicalSize<u32>> {
+        let size = self.take_reported();
+
+        if size.is_some() {
+            self.invalidate();
+        }
+
+        size
+    }
+
+    /// Takes the count of the sizes coalesced since the last time it was taken.
+    pub(crate) fn take_coalesced(&mut self) -> usize {
+        std::mem::replace(&mut self.coalesced, 0)
+    }
+
+    /// Has the swap chain recreated on the next frame.
+    pub(crate) fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Whether the swap chain has to be recreated.
+    pub(crate) fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Marks the swap chain as recreated in the current size.
+    pub(crate) fn recreated(&mut self) {
+        self.stale = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_the_last_size_of_a_live_resize() {
+        let mut resizes = Resizes::default();
+
+        for width in 400..=410 {
+            resizes.report(PhysicalSize::new(width, 300));
+        }
+
+        assert_eq!(resizes.take_reported(), Some(PhysicalSize::new(410, 300)));
+        assert_eq!(resizes.take_reported(), None);
+        assert_eq!(resizes.take_coalesced(), 10);
+        assert_eq!(resizes.take_coalesced(), 0);
+    }
+
+    #[test]
+    fn recreates_once_per_frame() {
+        let mut resizes = Resizes::default();
+        let mut recreations = 0;
+
+        for frame in 0..3 {
+            // a live resize reports several sizes between two frames
+            for width in 0..5 {
+                resizes.report(PhysicalSize::new(400 + frame * 10 + width, 300));
+            }
+
+            assert_eq!(
+                resizes.flush(),
+                Some(PhysicalSize::new(404 + frame * 10, 300))
+            );
+            assert_eq!(resizes.take_coalesced(), 4);
+
+            if resizes.is_stale() {
+                resizes.recreated();
+                recreations += 1;
+            }
+        }
+
+        assert_eq!(recreations, 3);
+
+        // nothing was reported since the last frame
+        assert_eq!(resizes.flush(), None);
+        assert!(!resizes.is_stale());
+    }
+}
@@ -0,0 +1,21 @@
+use iced_winit::futures::{self, Future};
+use iced_winit::Executor;
+
+use crate::MessageSender;
+
+/// The runtime of the commands and subscriptions of a hosted program. The messages they produce
+/// are sent to the event loop.
+pub(crate) type Runtime<M> = iced_winit::Runtime<ThreadPool, MessageSender<M>, M>;
+
+/// Runs the futures of the hosted program off the main thread.
+pub(crate) struct ThreadPool(futures::executor::ThreadPool);
+
+impl Executor for ThreadPool {
+    fn new() -> Result<Self, futures::io::Error> {
+        futures::executor::ThreadPool::new().map(ThreadPool)
+    }
+
+    fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        self.0.spawn_ok(future);
+    }
+}
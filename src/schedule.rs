@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant};
+
+use iced_winit::winit::event_loop::ControlFlow;
+
+/// When a [`ChildWindow`] requests to be redrawn.
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderSchedule {
+    /// Redraw on every iteration of the event loop.
+    Continuous,
+    /// Redraw only when the GUI has changed or has been marked dirty.
+    ///
+    /// The frames are redrawn whole, unless their damage is tracked, see
+    /// [`ChildWindowBuilder::damage_tracking`].
+    ///
+    /// [`ChildWindowBuilder::damage_tracking`]: struct.ChildWindowBuilder.html#method.damage_tracking
+    OnDemand,
+    /// Redraw on every iteration of the event loop, but no more than `fps` times per second.
+    Throttled { fps: u32 },
+    /// Redraw on every refresh of the display, then the event loop can just wait for events.
+    ///
+    /// It's driven by a `CVDisplayLink` on macOS. Elsewhere, or if the display link can't be
+    /// started, it's throttled to [`FALLBACK_FPS`].
+    ///
+    /// [`FALLBACK_FPS`]: constant.FALLBACK_FPS.html
+    DisplayLink,
+}
+
+/// The frame rate of [`RenderSchedule::DisplayLink`] without a display link.
+///
+/// [`RenderSchedule::DisplayLink`]: enum.RenderSchedule.html#variant.DisplayLink
+pub const FALLBACK_FPS: u32 = 60;
+
+impl Default for RenderSchedule {
+    fn default() -> Self {
+        RenderSchedule::OnDemand
+    }
+}
+
+/// The minimal interval between two frames rendered at `fps`.
+pub(crate) fn frame_interval(fps: u32) -> Duration {
+    Duration::from_secs(1) / fps.max(1)
+}
+
+/// Caps the `control_flow` of a schedule to `fps` after the `last_frame`.
+///
+/// It waits out the cap instead of polling, and wakes up for the frame the cap has held back if
+/// one is `pending`.
+pub(crate) fn cap(
+    control_flow: ControlFlow,
+    last_frame: Instant,
+    fps: u32,
+    pending: bool,
+) -> ControlFlow {
+    let next_frame = last_frame + frame_interval(fps);
+
+    match control_flow {
+        ControlFlow::Poll => ControlFlow::WaitUntil(next_frame),
+        ControlFlow::Wait if pending => ControlFlow::WaitUntil(next_frame),
+        ControlFlow::WaitUntil(instant) => ControlFlow::WaitUntil(instant.max(next_frame)),
+        _ => control_flow,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spaces_the_frames_by_the_rate() {
+        assert_eq!(frame_interval(30), Duration::from_nanos(33_333_333));
+        assert_eq!(
+            frame_interval(FALLBACK_FPS),
+            Duration::from_nanos(16_666_666)
+        );
+        // a rate of 0 can't divide
+        assert_eq!(frame_interval(0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn waits_out_the_cap_instead_of_polling() {
+        let last_frame = Instant::now();
+        let next_frame = last_frame + frame_interval(30);
+
+        assert_eq!(
+            cap(ControlFlow::Poll, last_frame, 30, false),
+            ControlFlow::WaitUntil(next_frame)
+        );
+        assert_eq!(
+            cap(ControlFlow::Wait, last_frame, 30, true),
+            ControlFlow::WaitUntil(next_frame)
+        );
+        // nothing to render
+        assert_eq!(
+            cap(ControlFlow::Wait, last_frame, 30, false),
+            ControlFlow::Wait
+        );
+    }
+
+    #[test]
+    fn never_wakes_up_sooner_than_the_cap() {
+        let last_frame = Instant::now();
+        let next_frame = last_frame + frame_interval(30);
+        let throttled = last_frame + frame_interval(60);
+        let later = last_frame + Duration::from_secs(1);
+
+        assert_eq!(
+            cap(ControlFlow::WaitUntil(throttled), last_frame, 30, false),
+            ControlFlow::WaitUntil(next_frame)
+        );
+        assert_eq!(
+            cap(ControlFlow::WaitUntil(later), last_frame, 30, false),
+            ControlFlow::WaitUntil(later)
+        );
+    }
+}
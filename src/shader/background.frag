@@ -0,0 +1,12 @@
+#version 450
+
+layout(set = 0, binding = 0) uniform texture2D t_Background;
+layout(set = 0, binding = 1) uniform sampler s_Background;
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Color;
+
+void main() {
+    o_Color = texture(sampler2D(t_Background, s_Background), v_Uv);
+}
@@ -0,0 +1,14 @@
+#version 450
+
+layout(set = 0, binding = 2) uniform Scale {
+    vec2 u_Scale;
+};
+
+layout(location = 0) out vec2 v_Uv;
+
+void main() {
+    // the corners of the texture, drawn as a triangle strip
+    v_Uv = vec2(gl_VertexIndex & 1, gl_VertexIndex >> 1);
+
+    gl_Position = vec4((v_Uv * 2.0 - 1.0) * vec2(1.0, -1.0) * u_Scale, 0.0, 1.0);
+}
@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How many of the last frames the frame rate is averaged over.
+const ROLLING_WINDOW: usize = 60;
+
+/// The timings of the frames of a [`ChildWindow`], e.g. to display or log while profiling.
+///
+/// The durations are measured on the CPU. wgpu can't query the GPU timestamps yet, so
+/// `submit_time` is how long the queue takes to accept the work, not how long the GPU executes
+/// it.
+///
+/// [`ChildWindow`]: struct.ChildWindow.html
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FrameStats {
+    /// How long the last update of the program took, including its layout.
+    pub update_time: Duration,
+    /// How long iced took to record the draw commands of the last frame.
+    pub draw_time: Duration,
+    /// How long the submission of the last frame took.
+    pub submit_time: Duration,
+    /// The frame rate averaged over the last 60 frames. 0 until two frames are rendered.
+    pub fps: f32,
+}
+
+/// Records the timings every frame.
+#[derive(Debug, Default)]
+pub(crate) struct FrameTimer {
+    stats: FrameStats,
+    frames: VecDeque<Instant>,
+    frame: u64,
+}
+
+impl FrameTimer {
+    pub(crate) fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    /// The number of the next frame, counted from 0.
+    pub(crate) fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub(crate) fn record_update(&mut self, update_time: Duration) {
+        self.stats.update_time = update_time;
+    }
+
+    pub(crate) fn record_frame(&mut self, draw_time: Duration, submit_time: Duration) {
+        let now = Instant::now();
+
+        if self.frames.len() == ROLLING_WINDOW {
+            let _ = self.frames.pop_front();
+        }
+
+        self.frames.push_back(now);
+        self.frame += 1;
+
+        self.stats.draw_time = draw_time;
+        self.stats.submit_time = submit_time;
+        self.stats.fps = match self.frames.front() {
+            Some(first) if self.frames.len() > 1 => {
+                (self.frames.len() - 1) as f32 / (now - *first).as_secs_f32()
+            }
+            _ => 0.0,
+        };
+    }
+}
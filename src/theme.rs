@@ -0,0 +1,31 @@
+/// The appearance of the system, which the hosted program can restyle to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::Light
+    }
+}
+
+/// The effective appearance of the application.
+///
+/// It's the default one if the appearance has no name, e.g. before macOS 10.14, which introduced
+/// the dark mode.
+#[cfg(target_os = "macos")]
+pub(crate) fn system_theme() -> Theme {
+    match unsafe { crate::cocoa_util::effective_appearance_name() } {
+        // e.g. NSAppearanceNameDarkAqua or NSAppearanceNameAccessibilityHighContrastDarkAqua
+        Some(name) if name.contains("Dark") => Theme::Dark,
+        Some(_) => Theme::Light,
+        None => Theme::default(),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn system_theme() -> Theme {
+    Theme::default()
+}
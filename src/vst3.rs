@@ -0,0 +1,228 @@
+//! An editor for VST3 plugins, implementing the lifecycle of `IPlugView` on top of a
+//! [`ChildWindow`].
+//!
+//! The `IPlugView` of the plugin forwards `isPlatformTypeSupported`, `attached`, `removed`,
+//! `onSize`, `getSize` and `canResize` to a [`Vst3View`], and calls [`Vst3View::tick`] from the
+//! timer it runs on the main thread of the host. No VST3 bindings are needed, the rectangles are
+//! read through [`ViewRect`].
+//!
+//! [`ChildWindow`]: ../struct.ChildWindow.html
+//! [`Vst3View`]: struct.Vst3View.html
+//! [`Vst3View::tick`]: struct.Vst3View.html#method.tick
+//! [`ViewRect`]: struct.ViewRect.html
+
+use std::ffi::c_void;
+
+use iced_wgpu::Renderer;
+use iced_winit::{winit::event_loop::EventLoop, Program, Size};
+
+use crate::{ChildWindow, ChildWindowBuilder, EmbedError, InitError};
+
+/// The `kPlatformTypeNSView` the parent of a macOS host is given with.
+pub const PLATFORM_TYPE_NSVIEW: &str = "NSView";
+
+/// The `kPlatformTypeHWND` the parent of a Windows host is given with.
+pub const PLATFORM_TYPE_HWND: &str = "HWND";
+
+/// The `ViewRect` of VST3, with the same layout, so a `*mut ViewRect` of the host can be cast
+/// to it.
+///
+/// It's in points on macOS and in physical pixels on Windows.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ViewRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl ViewRect {
+    /// A rectangle of the `width` and the `height` at the origin.
+    pub fn with_size(width: i32, height: i32) -> Self {
+        ViewRect {
+            left: 0,
+            top: 0,
+            right: width,
+            bottom: height,
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+}
+
+/// Whether a view can be attached to a parent of the `platform_type`, for
+/// `isPlatformTypeSupported`.
+pub fn is_platform_type_supported(platform_type: &str) -> bool {
+    (cfg!(target_os = "macos") && platform_type == PLATFORM_TYPE_NSVIEW)
+        || (cfg!(target_os = "windows") && platform_type == PLATFORM_TYPE_HWND)
+}
+
+/// A [`ChildWindow`] driven through the lifecycle of `IPlugView`.
+///
+/// The window is built with the builder `new_window` returns when the view is attached, and
+/// dropped when it's removed, because hosts destroy the parent right after. The state the
+/// editor has to keep across openings belongs to the plugin, e.g. in its parameters. The winit
+/// event loop lives as long as the view, one iteration runs per [`tick`].
+///
+/// [`ChildWindow`]: ../struct.ChildWindow.html
+/// [`tick`]: #method.tick
+pub struct Vst3View<P: 'static + Program<Renderer = Renderer>> {
+    new_window: Box<dyn FnMut() -> ChildWindowBuilder<P>>,
+    child_window: Option<ChildWindow<P>>,
+    event_loop: EventLoop<P::Message>,
+    size: ViewRect,
+    can_resize: bool,
+}
+
+impl<P: 'static + Program<Renderer = Renderer>> Vst3View<P> {
+    /// Creates a view of the `size`, which the host queries before attaching it. It's
+    /// resizable by default.
+    pub fn new(
+        size: ViewRect,
+        new_window: impl FnMut() -> ChildWindowBuilder<P> + 'static,
+    ) -> Self {
+        Vst3View {
+            new_window: Box::new(new_window),
+            child_window: None,
+            event_loop: EventLoop::with_user_event(),
+            size,
+            can_resize: true,
+        }
+    }
+
+    /// Sets whether the host may resize the view, for `canResize`.
+    pub fn resizable(mut self, can_resize: bool) -> Self {
+        self.can_resize = can_resize;
+        self
+    }
+
+    /// Builds the window and embeds it into the `parent` of the `platform_type`, for
+    /// `attached`.
+    ///
+    /// # Safety
+    ///
+    /// `parent` must be a valid `NSView` or `HWND`, matching the `platform_type`, which stays
+    /// valid until [`removed`], and it must be called on the main thread.
+    ///
+    /// [`removed`]: #method.removed
+    pub unsafe fn attached(
+        &mut self,
+        parent: *mut c_void,
+        platform_type: &str,
+    ) -> Result<(), InitError> {
+        if parent.is_null() || !is_platform_type_supported(platform_type) {
+            return Err(EmbedError::UnsupportedParent.into());
+        }
+
+        let builder = (self.new_window)();
+        self.child_window = Some(build_in(builder, &self.event_loop, parent, self.size)?);
+
+        Ok(())
+    }
+
+    /// Drops the window, for `removed`.
+    pub fn removed(&mut self) {
+        self.child_window = None;
+    }
+
+    /// Resizes the view to the `new_size` the host gives, for `onSize`.
+    pub fn on_size(&mut self, new_size: ViewRect) {
+        self.size = new_size;
+
+        if let Some(child_window) = &mut self.child_window {
+            let scale_factor = if cfg!(target_os = "macos") {
+                1.0
+            } else {
+                child_window.window().scale_factor()
+            };
+
+            child_window.resize(Size::new(
+                (new_size.width() as f64 / scale_factor) as f32,
+                (new_size.height() as f64 / scale_factor) as f32,
+            ));
+        }
+    }
+
+    /// The size of the view, for `getSize`.
+    pub fn get_size(&self) -> ViewRect {
+        self.size
+    }
+
+    /// Whether the host may resize the view, for `canResize`.
+    pub fn can_resize(&self) -> bool {
+        self.can_resize
+    }
+
+    /// Handles the pending events, updates the program and renders a frame if it's needed.
+    /// Returns whether a frame was rendered, `false` while the view isn't attached.
+    ///
+    /// Call it from a timer on the main thread, see [`ChildWindow::tick`].
+    ///
+    /// [`ChildWindow::tick`]: ../struct.ChildWindow.html#method.tick
+    pub fn tick(&mut self) -> bool {
+        match &mut self.child_window {
+            Some(child_window) => child_window.tick(&mut self.event_loop),
+            None => false,
+        }
+    }
+
+    /// The window of the view while it's attached, e.g. to queue the messages of parameter
+    /// changes.
+    pub fn child_window(&mut self) -> Option<&mut ChildWindow<P>> {
+        self.child_window.as_mut()
+    }
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn build_in<P: 'static + Program<Renderer = Renderer>>(
+    builder: ChildWindowBuilder<P>,
+    event_loop: &EventLoop<P::Message>,
+    parent: *mut c_void,
+    size: ViewRect,
+) -> Result<ChildWindow<P>, InitError> {
+    use cocoa::foundation::{NSPoint, NSRect, NSSize};
+
+    let mut child_window = builder.build_standalone(event_loop)?;
+
+    // only its view is shown, in the parent
+    child_window.window().set_visible(false);
+    child_window.embed_in_nsview(
+        parent as cocoa::base::id,
+        NSRect::new(
+            NSPoint::new(0.0, 0.0),
+            NSSize::new(size.width() as f64, size.height() as f64),
+        ),
+    );
+
+    Ok(child_window)
+}
+
+/// The window fills the parent, which the host has sized with `getSize`.
+#[cfg(target_os = "windows")]
+unsafe fn build_in<P: 'static + Program<Renderer = Renderer>>(
+    builder: ChildWindowBuilder<P>,
+    event_loop: &EventLoop<P::Message>,
+    parent: *mut c_void,
+    _size: ViewRect,
+) -> Result<ChildWindow<P>, InitError> {
+    let parent = crate::Win32Parent::new(parent as winapi::shared::windef::HWND);
+
+    builder.build(event_loop, &parent)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+unsafe fn build_in<P: 'static + Program<Renderer = Renderer>>(
+    _builder: ChildWindowBuilder<P>,
+    _event_loop: &EventLoop<P::Message>,
+    _parent: *mut c_void,
+    _size: ViewRect,
+) -> Result<ChildWindow<P>, InitError> {
+    Err(EmbedError::UnsupportedParent.into())
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "testing")]
+
+use iced_child_win::{controls::Controls, TestHarness};
+use iced_wgpu::Primitive;
+use iced_winit::{
+    winit::{
+        dpi::PhysicalPosition,
+        event::{DeviceId, ElementState, ModifiersState, MouseButton, WindowEvent},
+    },
+    Rectangle, Size,
+};
+
+/// The width of each of the three background sliders, which share a row of 500 units spaced
+/// by 20.
+const BACKGROUND_SLIDER_WIDTH: f32 = (500.0 - 2.0 * 20.0) / 3.0;
+
+/// The bounds of the first quad of the width of a background slider, the rail of the red one.
+fn red_rail(primitive: &Primitive) -> Option<Rectangle> {
+    match primitive {
+        Primitive::Group { primitives } => primitives.iter().find_map(red_rail),
+        Primitive::Quad { bounds, .. } if (bounds.width - BACKGROUND_SLIDER_WIDTH).abs() < 1.0 => {
+            Some(*bounds)
+        }
+        _ => None,
+    }
+}
+
+#[allow(deprecated)]
+fn cursor_moved(x: f32, y: f32) -> WindowEvent<'static> {
+    WindowEvent::CursorMoved {
+        device_id: unsafe { DeviceId::dummy() },
+        position: PhysicalPosition::new(x as f64, y as f64),
+        modifiers: ModifiersState::default(),
+    }
+}
+
+#[allow(deprecated)]
+fn left_button(state: ElementState) -> WindowEvent<'static> {
+    WindowEvent::MouseInput {
+        device_id: unsafe { DeviceId::dummy() },
+        state,
+        button: MouseButton::Left,
+        modifiers: ModifiersState::default(),
+    }
+}
+
+#[test]
+fn dragging_a_slider_changes_the_background_color() {
+    let mut harness = TestHarness::new(Controls::new(), Size::new(800, 600))
+        .expect("the test needs a GPU adapter");
+    harness.step();
+
+    let rail = red_rail(harness.current_primitive()).expect("the red slider isn't drawn");
+    let y = rail.y + rail.height / 2.0;
+    assert_eq!(harness.program().background_color().r, 1.0);
+
+    // the moves in between are coalesced, the drag ends left of the rail
+    harness.feed_event(cursor_moved(rail.x + rail.width / 2.0, y));
+    harness.feed_event(left_button(ElementState::Pressed));
+    harness.feed_event(cursor_moved(rail.x + rail.width / 4.0, y));
+    harness.feed_event(cursor_moved(rail.x - 10.0, y));
+    harness.feed_event(left_button(ElementState::Released));
+    harness.step();
+
+    let background_color = harness.program().background_color();
+    assert_eq!(background_color.r, 0.0);
+    assert_eq!(background_color.g, 0.5);
+}
@@ -0,0 +1,54 @@
+//! AppKit creates the windows only on the main thread, where the tests of libtest don't run, so
+//! it's run without the harness.
+
+#[cfg(target_os = "macos")]
+fn main() {
+    use cocoa::{
+        appkit::{NSView, NSWindow},
+        base::{id, nil},
+        foundation::{NSPoint, NSRect, NSSize},
+    };
+    use iced_child_win::{cocoa_util, CocoaParent, ParentSurface};
+    use raw_window_handle::{macos::MacOSHandle, RawWindowHandle};
+
+    unsafe {
+        let window = cocoa_util::create_window(
+            NSRect::new(NSPoint::new(100.0, 100.0), NSSize::new(400.0, 300.0)),
+            cocoa_util::Decorations::Titled,
+        );
+        let parent = CocoaParent::new(window);
+
+        // the title bar is on top of the content
+        let content = cocoa_util::content_bounds(window);
+        assert!(NSWindow::frame(window).size.height > content.size.height);
+        assert_eq!(parent.frame().height, 300.0);
+
+        let child: id = NSView::initWithFrame_(
+            NSView::alloc(nil),
+            NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(1.0, 1.0)),
+        );
+        parent
+            .attach_child(RawWindowHandle::MacOS(MacOSHandle {
+                ns_window: window as *mut _,
+                ns_view: child as *mut _,
+                ..MacOSHandle::empty()
+            }))
+            .expect("a view can be attached to a window");
+
+        // in the content view, not at the bottom of the frame of the window
+        let frame = NSView::frame(child);
+        assert_eq!(frame.origin.x, content.origin.x);
+        assert_eq!(frame.origin.y, content.origin.y);
+        assert_eq!(frame.size.width, content.size.width);
+        assert_eq!(frame.size.height, content.size.height);
+        assert_eq!(
+            cocoa_util::view_window(child).map(|window| window as usize),
+            Some(window as usize)
+        );
+
+        cocoa_util::close_window(window);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn main() {}
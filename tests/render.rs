@@ -0,0 +1,90 @@
+#![cfg(feature = "testing")]
+
+use iced_child_win::{linear_color, OffscreenRenderer};
+use iced_wgpu::{container, wgpu, Renderer};
+use iced_winit::{Background, Color, Command, Container, Element, Length, Program, Size, Space};
+
+const SIZE: Size<u32> = Size {
+    width: 16,
+    height: 16,
+};
+
+/// A program filling the whole frame with its color, or drawing nothing without one.
+struct Fill(Option<Color>);
+
+struct FillStyle(Option<Color>);
+
+impl container::StyleSheet for FillStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: self.0.map(Background::Color),
+            ..container::Style::default()
+        }
+    }
+}
+
+impl Program for Fill {
+    type Renderer = Renderer;
+    type Message = ();
+
+    fn update(&mut self, _message: ()) -> Command<()> {
+        Command::none()
+    }
+
+    fn view(&mut self) -> Element<(), Renderer> {
+        Container::new(Space::new(Length::Fill, Length::Fill))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(FillStyle(self.0))
+            .into()
+    }
+}
+
+/// The RGBA pixel in the middle of the `program` rendered over the `clear_color`.
+fn center_pixel(program: Fill, clear_color: wgpu::Color) -> [u8; 4] {
+    let mut renderer = OffscreenRenderer::new(program).expect("the test needs a GPU adapter");
+    renderer.set_clear_color(clear_color);
+
+    let pixels = renderer
+        .render_to_buffer(SIZE)
+        .expect("the frame can be read back");
+    let offset = ((SIZE.height / 2 * SIZE.width + SIZE.width / 2) * 4) as usize;
+
+    [
+        pixels[offset],
+        pixels[offset + 1],
+        pixels[offset + 2],
+        pixels[offset + 3],
+    ]
+}
+
+fn assert_close(actual: [u8; 4], expected: [u8; 4]) {
+    // the rounding of the GPU may differ by one
+    let close = actual
+        .iter()
+        .zip(&expected)
+        .all(|(actual, expected)| (*actual as i16 - *expected as i16).abs() <= 1);
+
+    assert!(close, "{:?} isn't {:?}", actual, expected);
+}
+
+#[test]
+fn a_converted_clear_color_matches_an_iced_fill() {
+    let color = Color::from_rgb(0.5, 0.25, 0.75);
+
+    let cleared = center_pixel(Fill(None), linear_color(color));
+    let filled = center_pixel(Fill(Some(color)), wgpu::Color::TRANSPARENT);
+
+    assert_close(cleared, filled);
+}
+
+#[test]
+fn an_iced_fill_keeps_its_color_in_an_srgb_format() {
+    // the sRGB formats encode what iced outputs in linear, the bytes are the ones of the color
+    let filled = center_pixel(
+        Fill(Some(Color::from_rgb8(0x80, 0x40, 0xc0))),
+        wgpu::Color::TRANSPARENT,
+    );
+
+    assert_close(filled, [0x80, 0x40, 0xc0, 0xff]);
+}